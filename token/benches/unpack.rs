@@ -0,0 +1,29 @@
+//! Measures `Account::unpack` throughput
+//!
+//! Run with `cargo bench --bench unpack`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use solana_program::{program_option::COption, program_pack::Pack, pubkey::Pubkey};
+use token::state::{Account, AccountState};
+
+fn bench_unpack(c: &mut Criterion) {
+    let account = Account {
+        mint: Pubkey::new_unique(),
+        owner: Pubkey::new_unique(),
+        amount: u64::MAX,
+        delegate: COption::Some(Pubkey::new_unique()),
+        state: AccountState::Initialized,
+        is_native: COption::Some(2_039_280),
+        delegated_amount: 42,
+        close_authority: COption::Some(Pubkey::new_unique()),
+    };
+    let mut data = vec![0; Account::LEN];
+    Account::pack(account, &mut data).unwrap();
+
+    c.bench_function("Account::unpack", |b| {
+        b.iter(|| Account::unpack(black_box(&data)).unwrap())
+    });
+}
+
+criterion_group!(benches, bench_unpack);
+criterion_main!(benches);
@@ -74,6 +74,29 @@ pub enum TokenError {
     /// Instruction does not support non-native tokens
     #[error("Instruction does not support non-native tokens")]
     NonNativeNotSupported,
+
+    // 20
+    /// Native account token amount is not backed by its lamport balance
+    #[error("Native account amount and lamports are out of sync")]
+    InvalidNativeAccount,
+    /// Transfers of this mint are paused
+    #[error("Transfers of this mint are paused")]
+    MintPaused,
+    /// Accounts of a pausable mint can only transfer with the mint supplied
+    #[error("Transfers of this mint must supply the mint")]
+    MintRequiredForTransfer,
+    /// The account's owner was fixed with `InitializeImmutableOwner`
+    #[error("Account owner cannot be changed")]
+    ImmutableOwner,
+    /// The mint's decimals exceed `MAX_DECIMALS`
+    #[error("Invalid number of mint decimals")]
+    InvalidMintDecimals,
+    /// The owner of an account cannot be its delegate
+    #[error("Invalid delegate")]
+    InvalidDelegate,
+    /// The fee doesn't match the mint's transfer fee
+    #[error("Fee does not match the mint's transfer fee")]
+    FeeMismatch,
 }
 
 impl From<TokenError> for ProgramError {
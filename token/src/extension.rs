@@ -0,0 +1,497 @@
+//! Extension types stored after the base `Mint` and `Account` state
+//!
+//! Extensions are laid out as type-length-value entries directly after the base
+//! state: a little-endian `u16` extension type, a little-endian `u16` value length
+//! and the value bytes. Zeroed space after the last entry is unused.
+
+use crate::error::TokenError;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use num_enum::TryFromPrimitive;
+use solana_program::{
+    program_error::ProgramError,
+    program_pack::{Pack, Sealed},
+    pubkey::Pubkey,
+};
+use std::convert::{TryFrom, TryInto};
+
+/// Length of the type and length header preceding each extension value
+const HEADER_LEN: usize = 4;
+
+/// Extensions that may be stored after the base state
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, PartialEq, TryFromPrimitive)]
+pub enum ExtensionType {
+    /// Marks unused space after the last extension
+    Uninitialized,
+    /// Cumulative amount of tokens burned from a mint, stored as a `u64`. Added by
+    /// `InitializeBurnedTotal` before the mint is initialized.
+    BurnedTotal,
+    /// Pause switch for transfers of a mint, stored as a [PausableConfig](struct.PausableConfig.html).
+    /// Added by `InitializePausableConfig` before the mint is initialized.
+    Pausable,
+    /// Slot in which a mint was initialized, stored as a `u64`. Added, zeroed, by
+    /// `InitializeCreatedSlot` before `InitializeMint` records the slot. It takes
+    /// 12 bytes after the base `Mint`, whose own layout is unchanged.
+    CreatedSlot,
+    /// Marks a token account of a mint with the `Pausable` extension. Carries no
+    /// value and is added when the account is initialized.
+    PausableAccount,
+    /// Marks a token account whose owner can never change. Carries no value and
+    /// is added by `InitializeImmutableOwner` before the account is initialized.
+    ImmutableOwner,
+    /// Fee schedule of a mint, stored as a [TransferFee](struct.TransferFee.html).
+    /// Added by `InitializeTransferFeeConfig` before the mint is initialized.
+    TransferFeeConfig,
+    /// Interest accruing on the UI amounts of a mint, stored as an
+    /// [InterestBearingConfig](struct.InterestBearingConfig.html). Added by
+    /// `InitializeInterestBearingConfig` before the mint is initialized.
+    InterestBearingConfig,
+    /// Marks a token account of a mint with the `TransferFeeConfig` extension.
+    /// Carries no value and is added when the account is initialized.
+    TransferFeeAccount,
+}
+
+impl ExtensionType {
+    /// Length of the value stored for this extension, excluding its header
+    pub fn value_len(&self) -> usize {
+        match self {
+            ExtensionType::Uninitialized
+            | ExtensionType::PausableAccount
+            | ExtensionType::ImmutableOwner
+            | ExtensionType::TransferFeeAccount => 0,
+            ExtensionType::BurnedTotal | ExtensionType::CreatedSlot => 8,
+            ExtensionType::Pausable => PausableConfig::LEN,
+            ExtensionType::TransferFeeConfig => TransferFee::LEN,
+            ExtensionType::InterestBearingConfig => InterestBearingConfig::LEN,
+        }
+    }
+
+    /// Account extensions that token accounts of a mint with `mint_extension_types`
+    /// must carry
+    pub fn get_required_account_extensions(
+        mint_extension_types: &[ExtensionType],
+    ) -> Vec<ExtensionType> {
+        mint_extension_types
+            .iter()
+            .filter_map(|extension_type| match extension_type {
+                ExtensionType::Pausable => Some(ExtensionType::PausableAccount),
+                ExtensionType::TransferFeeConfig => Some(ExtensionType::TransferFeeAccount),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Total data length of an account with base state of length `base_len`
+    /// followed by an entry for each of `extension_types`
+    pub fn get_account_len(base_len: usize, extension_types: &[ExtensionType]) -> usize {
+        extension_types
+            .iter()
+            .filter(|extension_type| **extension_type != ExtensionType::Uninitialized)
+            .fold(base_len, |len, extension_type| {
+                len + HEADER_LEN + extension_type.value_len()
+            })
+    }
+}
+
+/// Pause configuration of a mint.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PausableConfig {
+    /// Authority allowed to pause and resume transfers.
+    pub pause_authority: Pubkey,
+    /// Is `true` while transfers of the mint are paused.
+    pub paused: bool,
+}
+impl Sealed for PausableConfig {}
+impl Pack for PausableConfig {
+    const LEN: usize = 33;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 33];
+        let (pause_authority, paused) = array_refs![src, 32, 1];
+        Ok(PausableConfig {
+            pause_authority: Pubkey::new_from_array(*pause_authority),
+            paused: match paused {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+        })
+    }
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 33];
+        let (pause_authority_dst, paused_dst) = mut_array_refs![dst, 32, 1];
+        pause_authority_dst.copy_from_slice(self.pause_authority.as_ref());
+        paused_dst[0] = self.paused as u8;
+    }
+}
+
+/// Basis points making up the whole transferred amount
+pub const ONE_IN_BASIS_POINTS: u16 = 10_000;
+
+/// Direction in which a fractional transfer fee is rounded
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FeeRounding {
+    /// Round towards the fee collector
+    #[default]
+    Up,
+    /// Round towards the sender
+    Down,
+}
+
+/// Transfer fee schedule of a mint
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TransferFee {
+    /// Fee charged on each transfer, in basis points of the transferred amount
+    pub transfer_fee_basis_points: u16,
+    /// Upper bound of the fee charged on a single transfer
+    pub maximum_fee: u64,
+}
+
+impl TransferFee {
+    /// Computes the fee charged on transferring `amount`, capped at `maximum_fee`.
+    /// Returns `None` if the fee rate exceeds 100%.
+    pub fn calculate_fee(&self, amount: u64, rounding: FeeRounding) -> Option<u64> {
+        if self.transfer_fee_basis_points > ONE_IN_BASIS_POINTS {
+            return None;
+        }
+        // u64::MAX * 10_000 is far below u128::MAX, so none of this can overflow
+        let numerator = u128::from(amount) * u128::from(self.transfer_fee_basis_points);
+        let denominator = u128::from(ONE_IN_BASIS_POINTS);
+        let fee = match rounding {
+            FeeRounding::Up => numerator.div_ceil(denominator),
+            FeeRounding::Down => numerator / denominator,
+        };
+        // the fee never exceeds `amount`, so it always fits back into a u64
+        u64::try_from(fee).ok().map(|fee| fee.min(self.maximum_fee))
+    }
+}
+impl Sealed for TransferFee {}
+impl Pack for TransferFee {
+    const LEN: usize = 10;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 10];
+        let (transfer_fee_basis_points, maximum_fee) = array_refs![src, 2, 8];
+        Ok(TransferFee {
+            transfer_fee_basis_points: u16::from_le_bytes(*transfer_fee_basis_points),
+            maximum_fee: u64::from_le_bytes(*maximum_fee),
+        })
+    }
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 10];
+        let (transfer_fee_basis_points_dst, maximum_fee_dst) = mut_array_refs![dst, 2, 8];
+        *transfer_fee_basis_points_dst = self.transfer_fee_basis_points.to_le_bytes();
+        *maximum_fee_dst = self.maximum_fee.to_le_bytes();
+    }
+}
+
+/// Average length of a year in seconds, counting leap years
+pub const SECONDS_PER_YEAR: i64 = 60 * 60 * 24 * 36_524 / 100;
+
+/// Fixed-point unit of interest scales: 18 decimal places
+const SCALE: u128 = 1_000_000_000_000_000_000;
+
+/// ln(2) in units of `SCALE`
+const LN_2: i128 = 693_147_180_559_945_309;
+
+/// Interest configuration of a mint. Raw amounts never change; only their UI
+/// representation grows, compounded continuously from `initialization_timestamp`.
+///
+/// Interest is computed in fixed point with 18 decimal places, so that every
+/// validator gets the same result. The growth factor is accurate to about
+/// 1e-16 relative to its value, which is far below the rounding of the
+/// amounts to whole units.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct InterestBearingConfig {
+    /// Unix timestamp from which interest accrues
+    pub initialization_timestamp: i64,
+    /// Yearly interest rate in basis points, negative for a decaying amount.
+    /// There is no rate authority: the rate is fixed once the extension is added.
+    pub rate: i16,
+}
+
+impl InterestBearingConfig {
+    /// Factor by which amounts have grown from `initialization_timestamp` until
+    /// `unix_timestamp`, in units of `SCALE`, or `None` if it doesn't fit into a
+    /// `u128`
+    fn total_scale(&self, unix_timestamp: i64) -> Option<u128> {
+        let timespan = unix_timestamp.checked_sub(self.initialization_timestamp)?;
+        // |rate * timespan| < 2^78 and SCALE / ONE_IN_BASIS_POINTS < 2^47, so the
+        // exponent can't overflow
+        let exponent = i128::from(self.rate)
+            * i128::from(timespan)
+            * (SCALE / u128::from(ONE_IN_BASIS_POINTS)) as i128
+            / i128::from(SECONDS_PER_YEAR);
+        Self::exp(exponent)
+    }
+
+    /// e^`exponent`, both in units of `SCALE`, or `None` if that doesn't fit into
+    /// a `u128`
+    fn exp(exponent: i128) -> Option<u128> {
+        // e^x = 2^k * e^r with 0 <= r < ln(2), where the series of e^r converges
+        // within about 20 terms
+        let k = exponent.div_euclid(LN_2);
+        let r = exponent.rem_euclid(LN_2) as u128;
+        let mut term = SCALE;
+        let mut sum = SCALE;
+        let mut n = 1;
+        while term > 0 {
+            term = term * r / (SCALE * n);
+            sum += term;
+            n += 1;
+        }
+        if k >= 0 {
+            sum.checked_mul(1u128.checked_shl(u32::try_from(k).ok()?)?)
+        } else {
+            let shift = u32::try_from(-k).unwrap_or(u32::MAX);
+            Some(sum.checked_shr(shift).unwrap_or(0))
+        }
+    }
+
+    /// Returns `amount` with the interest accrued by `unix_timestamp`, rounded
+    /// down, or `None` if that doesn't fit into a `u64`
+    pub fn amount_with_interest(&self, amount: u64, unix_timestamp: i64) -> Option<u64> {
+        let scaled = u128::from(amount).checked_mul(self.total_scale(unix_timestamp)?)? / SCALE;
+        u64::try_from(scaled).ok()
+    }
+
+    /// Returns the raw amount that has grown to `amount_with_interest` by
+    /// `unix_timestamp`, rounded to the nearest unit, or `None` if that doesn't
+    /// fit into a `u64`
+    pub fn amount_without_interest(
+        &self,
+        amount_with_interest: u64,
+        unix_timestamp: i64,
+    ) -> Option<u64> {
+        let total_scale = self.total_scale(unix_timestamp)?;
+        if total_scale == 0 {
+            return None;
+        }
+        let amount = (u128::from(amount_with_interest) * SCALE + total_scale / 2) / total_scale;
+        u64::try_from(amount).ok()
+    }
+}
+impl Sealed for InterestBearingConfig {}
+impl Pack for InterestBearingConfig {
+    const LEN: usize = 10;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 10];
+        let (initialization_timestamp, rate) = array_refs![src, 8, 2];
+        Ok(InterestBearingConfig {
+            initialization_timestamp: i64::from_le_bytes(*initialization_timestamp),
+            rate: i16::from_le_bytes(*rate),
+        })
+    }
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 10];
+        let (initialization_timestamp_dst, rate_dst) = mut_array_refs![dst, 8, 2];
+        *initialization_timestamp_dst = self.initialization_timestamp.to_le_bytes();
+        *rate_dst = self.rate.to_le_bytes();
+    }
+}
+
+/// Splits account data into the base state of length `base_len` and the extension
+/// region that follows it
+pub fn split_extensions(data: &[u8], base_len: usize) -> Result<(&[u8], &[u8]), ProgramError> {
+    if data.len() < base_len {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(data.split_at(base_len))
+}
+
+/// Mutable variant of [split_extensions](fn.split_extensions.html)
+pub fn split_extensions_mut(
+    data: &mut [u8],
+    base_len: usize,
+) -> Result<(&mut [u8], &mut [u8]), ProgramError> {
+    if data.len() < base_len {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(data.split_at_mut(base_len))
+}
+
+/// Returns the value range of `extension_type` within `tlv_data`, if present
+fn find_extension(
+    tlv_data: &[u8],
+    extension_type: ExtensionType,
+) -> Result<Option<(usize, usize)>, ProgramError> {
+    let mut start = 0;
+    while start + HEADER_LEN <= tlv_data.len() {
+        let header = &tlv_data[start..start + HEADER_LEN];
+        let entry_type = u16::from_le_bytes([header[0], header[1]]);
+        if entry_type == ExtensionType::Uninitialized as u16 {
+            break;
+        }
+        let value_start = start + HEADER_LEN;
+        let value_end = value_start + u16::from_le_bytes([header[2], header[3]]) as usize;
+        if value_end > tlv_data.len() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if entry_type == extension_type as u16 {
+            return Ok(Some((value_start, value_end)));
+        }
+        start = value_end;
+    }
+    Ok(None)
+}
+
+/// Returns the offset of the first unused byte in `tlv_data`
+fn unused_offset(tlv_data: &[u8]) -> usize {
+    let mut start = 0;
+    while start + HEADER_LEN <= tlv_data.len() {
+        let header = &tlv_data[start..start + HEADER_LEN];
+        if u16::from_le_bytes([header[0], header[1]]) == ExtensionType::Uninitialized as u16 {
+            break;
+        }
+        start += HEADER_LEN + u16::from_le_bytes([header[2], header[3]]) as usize;
+    }
+    start
+}
+
+/// Lists the extensions stored in `tlv_data`, in the order they were added
+pub fn get_extension_types(tlv_data: &[u8]) -> Result<Vec<ExtensionType>, ProgramError> {
+    let mut extension_types = vec![];
+    let mut start = 0;
+    while start + HEADER_LEN <= tlv_data.len() {
+        let header = &tlv_data[start..start + HEADER_LEN];
+        let extension_type =
+            ExtensionType::try_from_primitive(u16::from_le_bytes([header[0], header[1]]))
+                .map_err(|_| ProgramError::InvalidAccountData)?;
+        if extension_type == ExtensionType::Uninitialized {
+            break;
+        }
+        start += HEADER_LEN + u16::from_le_bytes([header[2], header[3]]) as usize;
+        if start > tlv_data.len() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        extension_types.push(extension_type);
+    }
+    Ok(extension_types)
+}
+
+/// Returns the value bytes of `extension_type`, if present
+pub fn get_extension_bytes(
+    tlv_data: &[u8],
+    extension_type: ExtensionType,
+) -> Result<Option<&[u8]>, ProgramError> {
+    Ok(find_extension(tlv_data, extension_type)?.map(|(start, end)| &tlv_data[start..end]))
+}
+
+/// Returns the mutable value bytes of `extension_type`, if present
+pub fn get_extension_bytes_mut(
+    tlv_data: &mut [u8],
+    extension_type: ExtensionType,
+) -> Result<Option<&mut [u8]>, ProgramError> {
+    Ok(
+        find_extension(tlv_data, extension_type)?
+            .map(move |(start, end)| &mut tlv_data[start..end]),
+    )
+}
+
+/// Writes a zeroed `extension_type` entry of `len` bytes into the unused space of
+/// `tlv_data` and returns its value bytes
+pub fn init_extension(
+    tlv_data: &mut [u8],
+    extension_type: ExtensionType,
+    len: usize,
+) -> Result<&mut [u8], ProgramError> {
+    if extension_type == ExtensionType::Uninitialized {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if find_extension(tlv_data, extension_type)?.is_some() {
+        return Err(TokenError::AlreadyInUse.into());
+    }
+    let start = unused_offset(tlv_data);
+    let value_start = start + HEADER_LEN;
+    let value_end = value_start + len;
+    let len: u16 = len.try_into().map_err(|_| ProgramError::InvalidArgument)?;
+    if value_end > tlv_data.len() {
+        return Err(ProgramError::AccountDataTooSmall);
+    }
+    tlv_data[start..start + 2].copy_from_slice(&(extension_type as u16).to_le_bytes());
+    tlv_data[start + 2..value_start].copy_from_slice(&len.to_le_bytes());
+    let value = &mut tlv_data[value_start..value_end];
+    value.iter_mut().for_each(|byte| *byte = 0);
+    Ok(value)
+}
+
+/// Adds a new `extension_type` entry holding `value` to the unused space of
+/// `tlv_data`
+pub fn add_u64_extension(
+    tlv_data: &mut [u8],
+    extension_type: ExtensionType,
+    value: u64,
+) -> Result<(), ProgramError> {
+    init_extension(tlv_data, extension_type, 8)?.copy_from_slice(&value.to_le_bytes());
+    Ok(())
+}
+
+/// Reads a `u64` extension value
+pub fn get_u64_extension(
+    tlv_data: &[u8],
+    extension_type: ExtensionType,
+) -> Result<Option<u64>, ProgramError> {
+    get_extension_bytes(tlv_data, extension_type)?
+        .map(|value| {
+            value
+                .try_into()
+                .map(u64::from_le_bytes)
+                .map_err(|_| ProgramError::InvalidAccountData)
+        })
+        .transpose()
+}
+
+/// Overwrites an existing `u64` extension value
+pub fn set_u64_extension(
+    tlv_data: &mut [u8],
+    extension_type: ExtensionType,
+    value: u64,
+) -> Result<(), ProgramError> {
+    let bytes = get_extension_bytes_mut(tlv_data, extension_type)?
+        .ok_or(ProgramError::InvalidAccountData)?;
+    if bytes.len() != 8 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    bytes.copy_from_slice(&value.to_le_bytes());
+    Ok(())
+}
+
+/// Reads an extension value stored in its packed representation
+pub fn get_extension<V: Pack>(
+    tlv_data: &[u8],
+    extension_type: ExtensionType,
+) -> Result<Option<V>, ProgramError> {
+    get_extension_bytes(tlv_data, extension_type)?
+        .map(|value| {
+            if value.len() != V::LEN {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            V::unpack_from_slice(value)
+        })
+        .transpose()
+}
+
+/// Adds a new `extension_type` entry holding the packed `value` to the unused
+/// space of `tlv_data`
+pub fn add_extension<V: Pack>(
+    tlv_data: &mut [u8],
+    extension_type: ExtensionType,
+    value: &V,
+) -> Result<(), ProgramError> {
+    value.pack_into_slice(init_extension(tlv_data, extension_type, V::LEN)?);
+    Ok(())
+}
+
+/// Overwrites an existing extension value with its packed representation
+pub fn set_extension<V: Pack>(
+    tlv_data: &mut [u8],
+    extension_type: ExtensionType,
+    value: &V,
+) -> Result<(), ProgramError> {
+    let bytes = get_extension_bytes_mut(tlv_data, extension_type)?
+        .ok_or(ProgramError::InvalidAccountData)?;
+    if bytes.len() != V::LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    value.pack_into_slice(bytes);
+    Ok(())
+}
@@ -1,15 +1,21 @@
 //! Instruction types
 
-use crate::{check_program_account, error::TokenError};
+use crate::{
+    check_program_account,
+    error::TokenError,
+    id,
+    state::{Account, MAX_DECIMALS},
+};
 use solana_program::{
     instruction::{AccountMeta, Instruction},
     program_error::ProgramError,
     program_option::COption,
-    pubkey::Pubkey,
+    pubkey::{Pubkey, PUBKEY_BYTES},
     sysvar,
 };
 
-use std::convert::TryInto;
+use num_enum::TryFromPrimitive;
+use std::convert::{TryFrom, TryInto};
 use std::mem::size_of;
 
 /// Minimum number of multisignature signers (min N)
@@ -19,11 +25,22 @@ pub const MID_SIGNERS: usize = 1;
 pub const MAX_SIGNERS: usize = 11;
 
 /// Instructions supported by the token program
+///
+/// Tags 0 through 24 are encoded exactly as in the SPL Token program. The
+/// instructions this program adds on top of those are numbered from 100:
+/// Token-2022 assigns the tags following 24 to instructions of its own, and a
+/// client built for either program must never have an instruction decoded as
+/// a different one by the other.
 #[repr(C)]
 #[derive(Clone, Debug, PartialEq)]
-pub enum TokenInstruction {
-    /// Initializes a new mint and optionally deposits all the newly minted
-    /// tokens in an account.
+pub enum TokenInstruction<'a> {
+    /// Initializes a new mint. Data following the freeze authority is ignored,
+    /// as in SPL Token.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The mint to initialize.
+    ///   1. `[]` Rent sysvar
     InitializeMint {
         /// Number of base 10 digits to the right of the decimal place.
         decimals: u8,
@@ -32,7 +49,15 @@ pub enum TokenInstruction {
         /// The freeze authority/multisignature of the mint.
         freeze_authority: COption<Pubkey>,
     },
-    /// Initialize a new account to hold tokens.
+    /// Initialize a new account to hold tokens. If the mint is the native mint,
+    /// the lamports above the rent-exempt reserve become the account's balance.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The account to initialize.
+    ///   1. `[]` The mint this account will be associated with.
+    ///   2. `[]` The new account's owner/multisignature.
+    ///   3. `[]` Rent sysvar
     InitializeAccount,
 
     /// Initializes a multisignature account with N provided signers.
@@ -42,7 +67,12 @@ pub enum TokenInstruction {
     },
 
     /// Transfer tokens from one account to another either directly or via 
-    /// a delegate.
+    /// a delegate. Accounts of a mint with the `Pausable` extension must use
+    /// `TransferChecked` instead, which supplies the mint holding the pause,
+    /// and accounts of a mint with `TransferFeeConfig` `TransferCheckedWithFee`.
+    /// A `Transfer` from or to such an account fails with
+    /// `MintRequiredForTransfer`, unlike in SPL Token, so clients built for it
+    /// have to switch to `TransferChecked` for these mints.
     Transfer {
         /// The amount of tokens to transfer.
         amount: u64,
@@ -50,6 +80,10 @@ pub enum TokenInstruction {
 
     /// Approve a delegate. A delegate is given the authority over tokens on behalf
     /// of the source account's owner
+    ///
+    /// The approved amount is not limited by the source balance; transfers and
+    /// burns by the delegate still fail once the balance runs out. Approving zero
+    /// tokens still sets the delegate; `Revoke` removes it.
     Approve {
         /// The amount of tokens the delegate is approved for.
         amount: u64
@@ -59,10 +93,14 @@ pub enum TokenInstruction {
     Revoke,
 
     /// Sets a new authority of a mint or account
+    ///
+    /// Setting the mint or freeze authority of a mint to `None` disables it for
+    /// good: the supply becomes fixed, or accounts can no longer be frozen. An
+    /// account owner cannot be removed.
     SetAuthority {
         /// The type of authority to update.
         authority_type: AuthorityType,
-        /// The new authority
+        /// The new authority, or `None` to disable it
         new_authority: COption<Pubkey>
     },
 
@@ -82,6 +120,93 @@ pub enum TokenInstruction {
     /// Close an account by transferring all its SOL to the destination account
     CloseAccount,
 
+    /// Freeze an Initialized account using the Mint's freeze_authority (if set).
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The account to freeze.
+    ///   1. `[]` The token mint.
+    ///   2. `[signer]` The mint freeze authority, or its multisignature account.
+    ///   3. ..3+M `[signer]` M signer accounts, if the freeze authority is a multisig.
+    FreezeAccount,
+
+    /// Thaw a Frozen account using the Mint's freeze_authority (if set).
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The account to thaw.
+    ///   1. `[]` The token mint.
+    ///   2. `[signer]` The mint freeze authority, or its multisignature account.
+    ///   3. ..3+M `[signer]` M signer accounts, if the freeze authority is a multisig.
+    ThawAccount,
+
+    /// Transfers tokens from one account to another either directly or via a
+    /// delegate, like `Transfer`, but also checks the token mint and decimals.
+    /// Fails with `FeeMismatch` for a mint with `TransferFeeConfig`, whose fee
+    /// is only paid by `TransferCheckedWithFee`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The source account.
+    ///   1. `[]` The token mint.
+    ///   2. `[writable]` The destination account.
+    ///   3. `[signer]` The source account's owner/delegate, or its multisignature account.
+    ///   4. ..4+M `[signer]` M signer accounts, if the authority is a multisig.
+    TransferChecked {
+        /// The amount of tokens to transfer.
+        amount: u64,
+        /// Expected number of base 10 digits to the right of the decimal place.
+        decimals: u8,
+    },
+
+    /// Approves a delegate like `Approve`, but also checks the token mint and
+    /// decimals.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The source account.
+    ///   1. `[]` The token mint.
+    ///   2. `[]` The delegate.
+    ///   3. `[signer]` The source account owner, or its multisignature account.
+    ///   4. ..4+M `[signer]` M signer accounts, if the owner is a multisig.
+    ApproveChecked {
+        /// The amount of tokens the delegate is approved for.
+        amount: u64,
+        /// Expected number of base 10 digits to the right of the decimal place.
+        decimals: u8,
+    },
+
+    /// Mints new tokens to an account like `MintTo`, but also checks the
+    /// decimals.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The mint.
+    ///   1. `[writable]` The account to mint tokens to.
+    ///   2. `[signer]` The mint's minting authority, or its multisignature account.
+    ///   3. ..3+M `[signer]` M signer accounts, if the authority is a multisig.
+    MintToChecked {
+        /// The amount of new tokens to mint.
+        amount: u64,
+        /// Expected number of base 10 digits to the right of the decimal place.
+        decimals: u8,
+    },
+
+    /// Burns tokens like `Burn`, but also checks the token mint and decimals.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The account to burn from.
+    ///   1. `[writable]` The token mint.
+    ///   2. `[signer]` The account's owner/delegate, or its multisignature account.
+    ///   3. ..3+M `[signer]` M signer accounts, if the authority is a multisig.
+    BurnChecked {
+        /// The amount of tokens to burn.
+        amount: u64,
+        /// Expected number of base 10 digits to the right of the decimal place.
+        decimals: u8,
+    },
+
     /// Like InitializeAccount, but the owner pubkey is passed via instruction data
     /// rather than the accounts list. This variant may be preferable when using
     /// Cross Program Invocation from an instruction that does not need the owner's
@@ -97,6 +222,17 @@ pub enum TokenInstruction {
         owner: Pubkey,
     },
 
+    /// Like InitializeAccount2, but does not require the Rent sysvar to be provided
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]`  The account to initialize.
+    ///   1. `[]` The mint this account will be associated with.
+    InitializeAccount3 {
+        /// The new account's owner/multisignature.
+        owner: Pubkey,
+    },
+
     /// Like InitializeMultisig, but does not require the Rent sysvar to be provided
     ///
     /// Accounts expected by this instruction:
@@ -124,12 +260,254 @@ pub enum TokenInstruction {
         /// The freeze authority/multisignature of the mint.
         freeze_authority: COption<Pubkey>
 
-    }
+    },
+
+    /// Given a wrapped / native token account (a special token account whose
+    /// balance is backed by its lamports) updates its amount field based on
+    /// the account's underlying `lamports`. This is useful if a non-wrapped SOL
+    /// account uses `system_instruction::transfer` to move lamports to a
+    /// wrapped token account, and needs to have its token `amount` field
+    /// updated.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The native token account to sync with its underlying
+    ///      lamports.
+    SyncNative,
+
+    /// Fixes the owner of a token account so `SetAuthority` can never change it.
+    /// Must be run on a new account before it is initialized, which requires
+    /// room for the marker beyond the base account length.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The account to initialize.
+    InitializeImmutableOwner,
+
+    /// Gets the data length a token account of the given mint requires, including
+    /// the extensions the mint calls for. The length is returned as a
+    /// little-endian `u64` via `set_return_data`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The mint to calculate for.
+    GetAccountDataSize,
+
+    /// Converts a raw amount of tokens to its decimal representation using the
+    /// mint's decimals, with trailing zeros trimmed. For a mint with an
+    /// `InterestBearingConfig`, the amount includes the interest accrued by the
+    /// current `Clock` timestamp. The string is returned as UTF-8 via
+    /// `set_return_data`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The mint to convert the amount for.
+    AmountToUiAmount {
+        /// The amount of tokens to convert.
+        amount: u64,
+    },
+
+    /// Converts a decimal amount of tokens to the raw amount using the mint's
+    /// decimals, undoing the interest of a mint with an `InterestBearingConfig`
+    /// like `AmountToUiAmount` adds it. The amount is returned as a
+    /// little-endian `u64` via `set_return_data`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The mint to convert the amount for.
+    UiAmountToAmount {
+        /// The decimal amount of tokens to convert.
+        ui_amount: &'a str,
+    },
+    /// Adds the `BurnedTotal` extension to a new mint, so that burns from it
+    /// add up the tokens burned over the mint's lifetime. Must be run before
+    /// the mint is initialized, which requires room for the extension beyond
+    /// the base mint length.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The mint to initialize.
+    InitializeBurnedTotal,
+
+    /// Reads the supply and decimals of a mint, the total burned from it and the
+    /// slot it was created in. Returns via `set_return_data` 25 bytes: the supply
+    /// as a little-endian `u64`, the decimals, then the burned total and the
+    /// creation slot as little-endian `u64`s, each zero for mints without the
+    /// `BurnedTotal` or `CreatedSlot` extension.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The mint to read.
+    GetMintInfo,
+
+    /// Pauses or resumes transfers of a mint carrying the `Pausable` extension.
+    /// Minting and burning are unaffected so the issuer can remediate while paused.
+    ///
+    /// `Transfer` does not supply the mint, so accounts of a pausable mint
+    /// are refused it and must use the checked transfers.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The mint.
+    ///   1. `[signer]` The mint's pause authority, or its multisignature account.
+    ///   2. ..2+M `[signer]` M signer accounts, if the pause authority is a multisig.
+    SetPause {
+        /// Whether transfers should be paused.
+        paused: bool,
+    },
+
+    /// Adds the `Pausable` extension to a new mint, so that `pause_authority`
+    /// can pause and resume its transfers with `SetPause`. Must be run before
+    /// the mint is initialized, which requires room for the extension beyond
+    /// the base mint length.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The mint to initialize.
+    InitializePausableConfig {
+        /// The authority allowed to pause and resume transfers.
+        pause_authority: Pubkey,
+    },
+
+    /// Moves the lamports held by a non-native account above its rent-exempt
+    /// minimum to a destination. Native accounts are rejected, as their lamports
+    /// back the token balance.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The source account.
+    ///   1. `[writable]` The destination account.
+    ///   2. `[signer]` The source account's owner, or its multisignature account.
+    ///   3. ..3+M `[signer]` M signer accounts, if the owner is a multisig.
+    WithdrawExcessLamports,
+
+    /// Adds the `CreatedSlot` extension to a new mint, so that `InitializeMint`
+    /// records the slot the mint is created in. Must be run before the mint is
+    /// initialized, which requires room for the extension beyond the base mint
+    /// length.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The mint to initialize.
+    InitializeCreatedSlot,
+
+    /// Burns the remaining balance of a non-native account and closes it,
+    /// transferring its lamports to the destination. `amount` must equal the
+    /// account balance; if the burn would leave tokens behind, the instruction
+    /// fails without burning anything. The authority must be allowed both to
+    /// burn from and to close the account.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The account to burn from and close.
+    ///   1. `[writable]` The token mint.
+    ///   2. `[writable]` The destination account.
+    ///   3. `[signer]` The account's owner or close authority, or its multisignature account.
+    ///   4. ..4+M `[signer]` M signer accounts, if the authority is a multisig.
+    BurnAndClose {
+        /// The amount of tokens to burn.
+        amount: u64,
+    },
+
+    /// Reads the token balance of an account, for CPI callers that want the
+    /// post-transfer balances without deserializing the account themselves. The
+    /// balance is returned as a little-endian `u64` via `set_return_data`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The token account to read.
+    GetAccountBalance,
+
+    /// Reads the state of a token account so that CPI callers can tell whether
+    /// it is frozen or native before acting on it. Returns via `set_return_data`
+    /// 10 bytes: the `AccountState` byte, the `is_native` flag as 0 or 1, and the
+    /// balance as a little-endian `u64`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The token account to read.
+    GetAccountState,
+
+    /// Transfers tokens like `TransferChecked`, but skims `fee` of the `amount`
+    /// off to a fee collector account of the same mint; the destination receives
+    /// `amount - fee`. If the mint has a `TransferFeeConfig`, `fee` must be the
+    /// fee it charges on `amount`, rounded up. Native accounts are not supported.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The source account.
+    ///   1. `[]` The token mint.
+    ///   2. `[writable]` The destination account.
+    ///   3. `[writable]` The fee collector account.
+    ///   4. `[signer]` The source account's owner/delegate, or its multisignature account.
+    ///   5. ..5+M `[signer]` M signer accounts, if the authority is a multisig.
+    TransferCheckedWithFee {
+        /// The amount of tokens debited from the source, including the fee.
+        amount: u64,
+        /// Expected number of base 10 digits to the right of the decimal place.
+        decimals: u8,
+        /// The part of `amount` paid to the fee collector.
+        fee: u64,
+    },
+
+    /// Adds the `TransferFeeConfig` extension to a new mint, so that its
+    /// `TransferCheckedWithFee` transfers are charged the given fee and its
+    /// `Transfer` and `TransferChecked` transfers are refused. Must be run
+    /// before the mint is initialized, which requires room for the extension
+    /// beyond the base mint length. Token accounts of the mint carry a
+    /// `TransferFeeAccount` marker, which needs room as well.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The mint to initialize.
+    InitializeTransferFeeConfig {
+        /// Fee charged on each transfer, in basis points of the transferred amount.
+        transfer_fee_basis_points: u16,
+        /// Upper bound of the fee charged on a single transfer.
+        maximum_fee: u64,
+    },
+
+    /// Adds the `InterestBearingConfig` extension to a new mint, so that its UI
+    /// amounts accrue interest from the current `Clock` timestamp. Must be run
+    /// before the mint is initialized, which requires room for the extension
+    /// beyond the base mint length. The rate can't be changed afterwards.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The mint to initialize.
+    InitializeInterestBearingConfig {
+        /// Yearly interest rate in basis points, negative for a decaying amount.
+        rate: i16,
+    },
+
+    /// Initializes a new mint like `InitializeMint` and deposits its whole
+    /// initial supply in a token account within the same instruction, so the
+    /// mint never exists without its supply.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The mint to initialize.
+    ///   1. `[]` Rent sysvar
+    ///   2. `[writable]` The token account receiving `initial_supply`. An
+    ///      uninitialized account is initialized for the mint, like with
+    ///      `InitializeAccount`; an initialized one must belong to the mint.
+    ///   3. `[]` The receiving account's owner/multisignature, used when the
+    ///      account is initialized by this instruction.
+    InitializeMintWithSupply {
+        /// Number of base 10 digits to the right of the decimal place.
+        decimals: u8,
+        /// The authority/multisignature to mint tokens.
+        mint_authority: Pubkey,
+        /// The freeze authority/multisignature of the mint.
+        freeze_authority: COption<Pubkey>,
+        /// Tokens minted to the receiving account as part of initialization.
+        initial_supply: u64,
+    },
 }
 
-impl TokenInstruction {
+impl<'a> TokenInstruction<'a> {
     /// Unpacks a byte buffer into a [TokenInstuction](enum.TokenInstruction.html).
-    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+    pub fn unpack(input: &'a [u8]) -> Result<Self, ProgramError> {
         use TokenError::InvalidInstruction;
 
         let (&tag, rest) = input.split_first().ok_or(InvalidInstruction)?;
@@ -144,19 +522,213 @@ impl TokenInstruction {
                     decimals
                 }
             }
-            // 1 => Self::InitializeAccount,
-            // 2 => {
-            //     let &m = rest.get(0).ok_or(InvalidInstruction)?;
-            //     Self::InitializeMultisig{m}
-            // }
-
+            1 => Self::InitializeAccount,
+            2 => {
+                let &m = rest.first().ok_or(InvalidInstruction)?;
+                Self::InitializeMultisig { m }
+            }
+            3 | 4 | 7 | 8 => {
+                let (amount, _rest) = Self::unpack_u64(rest)?;
+                match tag {
+                    3 => Self::Transfer { amount },
+                    4 => Self::Approve { amount },
+                    7 => Self::MintTo { amount },
+                    8 => Self::Burn { amount },
+                    _ => unreachable!(),
+                }
+            }
+            5 => Self::Revoke,
+            6 => {
+                let (authority_type, rest) = rest
+                    .split_first()
+                    .ok_or_else(|| ProgramError::from(InvalidInstruction))
+                    .and_then(|(&t, rest)| Ok((AuthorityType::try_from(t)?, rest)))?;
+                let (new_authority, _rest) = Self::unpack_pubkey_option(rest)?;
+                Self::SetAuthority {
+                    authority_type,
+                    new_authority,
+                }
+            }
+            9 => Self::CloseAccount,
+            10 => Self::FreezeAccount,
+            11 => Self::ThawAccount,
+            12..=15 => {
+                let (amount, rest) = Self::unpack_u64(rest)?;
+                let &decimals = rest.first().ok_or(InvalidInstruction)?;
+                match tag {
+                    12 => Self::TransferChecked { amount, decimals },
+                    13 => Self::ApproveChecked { amount, decimals },
+                    14 => Self::MintToChecked { amount, decimals },
+                    15 => Self::BurnChecked { amount, decimals },
+                    _ => unreachable!(),
+                }
+            }
+            16 => {
+                let (owner, _rest) = Self::unpack_pubkey(rest)?;
+                Self::InitializeAccount2 { owner }
+            }
+            17 => Self::SyncNative,
+            18 => {
+                let (owner, _rest) = Self::unpack_pubkey(rest)?;
+                Self::InitializeAccount3 { owner }
+            }
+            19 => {
+                let &m = rest.first().ok_or(InvalidInstruction)?;
+                Self::InitializeMultisig2 { m }
+            }
+            20 => {
+                let (&decimals, rest) = rest.split_first().ok_or(InvalidInstruction)?;
+                let (mint_authority, rest) = Self::unpack_pubkey(rest)?;
+                let (freeze_authority, _rest) = Self::unpack_pubkey_option(rest)?;
+                Self::InitializeMint2 {
+                    mint_authority,
+                    freeze_authority,
+                    decimals,
+                }
+            }
+            21 => Self::GetAccountDataSize,
+            22 => Self::InitializeImmutableOwner,
+            23 => {
+                let (amount, _rest) = Self::unpack_u64(rest)?;
+                Self::AmountToUiAmount { amount }
+            }
+            24 => {
+                let ui_amount = std::str::from_utf8(rest).map_err(|_| InvalidInstruction)?;
+                Self::UiAmountToAmount { ui_amount }
+            }
+            100 => Self::InitializeBurnedTotal,
+            101 => Self::GetMintInfo,
+            102 => {
+                let paused = match rest.first() {
+                    Some(0) => false,
+                    Some(1) => true,
+                    _ => return Err(InvalidInstruction.into()),
+                };
+                Self::SetPause { paused }
+            }
+            103 => {
+                let (pause_authority, _rest) = Self::unpack_pubkey(rest)?;
+                Self::InitializePausableConfig { pause_authority }
+            }
+            104 => Self::WithdrawExcessLamports,
+            105 => Self::InitializeCreatedSlot,
+            106 => {
+                let (amount, _rest) = Self::unpack_u64(rest)?;
+                Self::BurnAndClose { amount }
+            }
+            107 => Self::GetAccountBalance,
+            108 => Self::GetAccountState,
+            109 => {
+                let (amount, rest) = Self::unpack_u64(rest)?;
+                let (&decimals, rest) = rest.split_first().ok_or(InvalidInstruction)?;
+                let (fee, _rest) = Self::unpack_u64(rest)?;
+                Self::TransferCheckedWithFee {
+                    amount,
+                    decimals,
+                    fee,
+                }
+            }
+            110 => {
+                let transfer_fee_basis_points = rest
+                    .get(..2)
+                    .and_then(|slice| slice.try_into().ok())
+                    .map(u16::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+                let (maximum_fee, _rest) = Self::unpack_u64(&rest[2..])?;
+                Self::InitializeTransferFeeConfig {
+                    transfer_fee_basis_points,
+                    maximum_fee,
+                }
+            }
+            111 => {
+                let rate = rest
+                    .get(..2)
+                    .and_then(|slice| slice.try_into().ok())
+                    .map(i16::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+                Self::InitializeInterestBearingConfig { rate }
+            }
+            112 => {
+                let (&decimals, rest) = rest.split_first().ok_or(InvalidInstruction)?;
+                let (mint_authority, rest) = Self::unpack_pubkey(rest)?;
+                let (freeze_authority, rest) = Self::unpack_pubkey_option(rest)?;
+                let (initial_supply, _rest) = Self::unpack_u64(rest)?;
+                Self::InitializeMintWithSupply {
+                    decimals,
+                    mint_authority,
+                    freeze_authority,
+                    initial_supply,
+                }
+            }
             _ => return Err(TokenError::InvalidInstruction.into()),
         })
     }
 
     /// Packs a [TokenInstruction](enum.TokenInstruction.html) into a byte buffer.
     pub fn pack(&self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(size_of::<Self>());
+        let mut buf = Vec::with_capacity(self.packed_len());
+        self.pack_into(&mut buf);
+        buf
+    }
+
+    /// Returns the number of bytes [pack](#method.pack) produces for this
+    /// instruction, tag included, without packing it.
+    pub fn packed_len(&self) -> usize {
+        let pubkey_option_len = |value: &COption<Pubkey>| match value {
+            COption::Some(_) => 1 + PUBKEY_BYTES,
+            COption::None => 1,
+        };
+        1 + match self {
+            Self::InitializeMint {
+                freeze_authority, ..
+            }
+            | Self::InitializeMint2 {
+                freeze_authority, ..
+            } => 1 + PUBKEY_BYTES + pubkey_option_len(freeze_authority),
+            Self::InitializeMintWithSupply {
+                freeze_authority, ..
+            } => 1 + PUBKEY_BYTES + pubkey_option_len(freeze_authority) + size_of::<u64>(),
+            Self::SetAuthority { new_authority, .. } => 1 + pubkey_option_len(new_authority),
+            Self::InitializeAccount2 { .. }
+            | Self::InitializeAccount3 { .. }
+            | Self::InitializePausableConfig { .. } => PUBKEY_BYTES,
+            Self::InitializeMultisig { .. }
+            | Self::InitializeMultisig2 { .. }
+            | Self::SetPause { .. } => 1,
+            Self::Transfer { .. }
+            | Self::Approve { .. }
+            | Self::MintTo { .. }
+            | Self::Burn { .. }
+            | Self::AmountToUiAmount { .. }
+            | Self::BurnAndClose { .. } => size_of::<u64>(),
+            Self::TransferChecked { .. }
+            | Self::ApproveChecked { .. }
+            | Self::MintToChecked { .. }
+            | Self::BurnChecked { .. } => size_of::<u64>() + 1,
+            Self::TransferCheckedWithFee { .. } => 2 * size_of::<u64>() + 1,
+            Self::InitializeTransferFeeConfig { .. } => size_of::<u16>() + size_of::<u64>(),
+            Self::InitializeInterestBearingConfig { .. } => size_of::<i16>(),
+            Self::UiAmountToAmount { ui_amount } => ui_amount.len(),
+            Self::InitializeAccount
+            | Self::Revoke
+            | Self::CloseAccount
+            | Self::FreezeAccount
+            | Self::ThawAccount
+            | Self::SyncNative
+            | Self::GetAccountDataSize
+            | Self::InitializeImmutableOwner
+            | Self::WithdrawExcessLamports
+            | Self::GetAccountBalance
+            | Self::GetAccountState
+            | Self::InitializeBurnedTotal
+            | Self::GetMintInfo
+            | Self::InitializeCreatedSlot => 0,
+        }
+    }
+
+    /// Appends the packed [TokenInstruction](enum.TokenInstruction.html) to `buf`,
+    /// so that one buffer can be reused across many instructions.
+    pub fn pack_into(&self, buf: &mut Vec<u8>) {
         match self {
             &Self::InitializeMint {
                 ref mint_authority,
@@ -166,7 +738,7 @@ impl TokenInstruction {
                 buf.push(0);
                 buf.push(decimals);
                 buf.extend_from_slice(mint_authority.as_ref());
-                Self::pack_pubkey_option(freeze_authority, &mut buf);
+                Self::pack_pubkey_option(freeze_authority, buf);
             }
 
             Self::InitializeAccount => buf.push(1),
@@ -182,29 +754,147 @@ impl TokenInstruction {
                 buf.push(4);
                 buf.extend_from_slice(&amount.to_le_bytes());
             }
-            TokenInstruction::InitializeMint { decimals, mint_authority, freeze_authority } => todo!(),
-            TokenInstruction::InitializeAccount => todo!(),
-            TokenInstruction::InitializeMultisig { m } => todo!(),
-            TokenInstruction::Transfer { amount } => todo!(),
-            TokenInstruction::Approve { amount } => todo!(),
-            TokenInstruction::Revoke => todo!(),
-            TokenInstruction::SetAuthority { authority_type, new_authority } => todo!(),
-            TokenInstruction::MintTo { amount } => todo!(),
-            TokenInstruction::Burn { amount } => todo!(),
-            TokenInstruction::CloseAccount => todo!(),
-            TokenInstruction::InitializeAccount2 { owner } => todo!(),
-            TokenInstruction::InitializeMultisig2 { m } => todo!(),
-            TokenInstruction::InitializeMint2 { decimals, mint_authority, freeze_authority } => todo!(),
-            // _ => buf.push(0)
-            
+            Self::Revoke => buf.push(5),
+            Self::SetAuthority {
+                authority_type,
+                ref new_authority,
+            } => {
+                buf.push(6);
+                buf.push(u8::from(*authority_type));
+                Self::pack_pubkey_option(new_authority, buf);
+            }
+            &Self::MintTo { amount } => {
+                buf.push(7);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            &Self::Burn { amount } => {
+                buf.push(8);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            Self::CloseAccount => buf.push(9),
+            Self::FreezeAccount => buf.push(10),
+            Self::ThawAccount => buf.push(11),
+            &Self::TransferChecked { amount, decimals } => {
+                buf.push(12);
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.push(decimals);
+            }
+            &Self::ApproveChecked { amount, decimals } => {
+                buf.push(13);
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.push(decimals);
+            }
+            &Self::MintToChecked { amount, decimals } => {
+                buf.push(14);
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.push(decimals);
+            }
+            &Self::BurnChecked { amount, decimals } => {
+                buf.push(15);
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.push(decimals);
+            }
+            Self::InitializeAccount2 { owner } => {
+                buf.push(16);
+                buf.extend_from_slice(owner.as_ref());
+            }
+            Self::SyncNative => buf.push(17),
+            Self::InitializeAccount3 { owner } => {
+                buf.push(18);
+                buf.extend_from_slice(owner.as_ref());
+            }
+            &Self::InitializeMultisig2 { m } => {
+                buf.push(19);
+                buf.push(m);
+            }
+            &Self::InitializeMint2 {
+                ref mint_authority,
+                ref freeze_authority,
+                decimals,
+            } => {
+                buf.push(20);
+                buf.push(decimals);
+                buf.extend_from_slice(mint_authority.as_ref());
+                Self::pack_pubkey_option(freeze_authority, buf);
+            }
+            Self::GetAccountDataSize => buf.push(21),
+            Self::InitializeImmutableOwner => buf.push(22),
+            &Self::AmountToUiAmount { amount } => {
+                buf.push(23);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            Self::UiAmountToAmount { ui_amount } => {
+                buf.push(24);
+                buf.extend_from_slice(ui_amount.as_bytes());
+            }
+            Self::InitializeBurnedTotal => buf.push(100),
+            Self::GetMintInfo => buf.push(101),
+            &Self::SetPause { paused } => {
+                buf.push(102);
+                buf.push(paused as u8);
+            }
+            Self::InitializePausableConfig { pause_authority } => {
+                buf.push(103);
+                buf.extend_from_slice(pause_authority.as_ref());
+            }
+            Self::WithdrawExcessLamports => buf.push(104),
+            Self::InitializeCreatedSlot => buf.push(105),
+            &Self::BurnAndClose { amount } => {
+                buf.push(106);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            Self::GetAccountBalance => buf.push(107),
+            Self::GetAccountState => buf.push(108),
+            &Self::TransferCheckedWithFee {
+                amount,
+                decimals,
+                fee,
+            } => {
+                buf.push(109);
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.push(decimals);
+                buf.extend_from_slice(&fee.to_le_bytes());
+            }
+            &Self::InitializeTransferFeeConfig {
+                transfer_fee_basis_points,
+                maximum_fee,
+            } => {
+                buf.push(110);
+                buf.extend_from_slice(&transfer_fee_basis_points.to_le_bytes());
+                buf.extend_from_slice(&maximum_fee.to_le_bytes());
+            }
+            &Self::InitializeInterestBearingConfig { rate } => {
+                buf.push(111);
+                buf.extend_from_slice(&rate.to_le_bytes());
+            }
+            &Self::InitializeMintWithSupply {
+                ref mint_authority,
+                ref freeze_authority,
+                decimals,
+                initial_supply,
+            } => {
+                buf.push(112);
+                buf.push(decimals);
+                buf.extend_from_slice(mint_authority.as_ref());
+                Self::pack_pubkey_option(freeze_authority, buf);
+                buf.extend_from_slice(&initial_supply.to_le_bytes());
+            }
         };
-        buf
+    }
+
+    fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
+        let value = input
+            .get(..8)
+            .and_then(|slice| slice.try_into().ok())
+            .map(u64::from_le_bytes)
+            .ok_or(TokenError::InvalidInstruction)?;
+        Ok((value, &input[8..]))
     }
 
     fn unpack_pubkey(input: &[u8]) -> Result<(Pubkey, &[u8]), ProgramError> {
         if input.len() >= 32 {
             let (key, rest) = input.split_at(32);
-            let pk = Pubkey::new(key);
+            let pk = Pubkey::try_from(key).map_err(|_| TokenError::InvalidInstruction)?;
             Ok((pk, rest))
         } else {
             Err(TokenError::InvalidInstruction.into())
@@ -216,7 +906,7 @@ impl TokenInstruction {
             Option::Some((&0, rest)) => Ok((COption::None, rest)),
             Option::Some((&1, rest)) if rest.len() >= 32 => {
                 let (key, rest) = rest.split_at(32);
-                let pk = Pubkey::new(key);
+                let pk = Pubkey::try_from(key).map_err(|_| TokenError::InvalidInstruction)?;
                 Ok((COption::Some(pk), rest))
             }
             _ => Err(TokenError::InvalidInstruction.into())
@@ -236,7 +926,7 @@ impl TokenInstruction {
 
 /// Specifies the authority type for SetAuthority instruction
 #[repr(u8)]
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum AuthorityType {
     /// Authority to mint new tokens
     MintTokens,
@@ -251,17 +941,21 @@ pub enum AuthorityType {
     CloseAccount,
 }
 
-impl AuthorityType {
-    fn into(&self) -> u8 {
-        match self {
+impl From<AuthorityType> for u8 {
+    fn from(authority_type: AuthorityType) -> Self {
+        match authority_type {
             AuthorityType::MintTokens => 0,
             AuthorityType::FreezeAccount => 1,
             AuthorityType::AccountOwner => 2,
             AuthorityType::CloseAccount => 3
         }
     }
+}
+
+impl TryFrom<u8> for AuthorityType {
+    type Error = ProgramError;
 
-    fn from(index: u8) -> Result<Self, ProgramError> {
+    fn try_from(index: u8) -> Result<Self, Self::Error> {
         match index {
             0 => Ok(AuthorityType::MintTokens),
             1 => Ok(AuthorityType::FreezeAccount),
@@ -272,8 +966,104 @@ impl AuthorityType {
     }
 }
 
-/// Creates a `InitializeMint` instruction.
-pub fn initialize_mint(
+/// The kind of a [TokenInstruction](enum.TokenInstruction.html), as identified by its
+/// tag byte
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, TryFromPrimitive)]
+pub enum InstructionType {
+    /// A [InitializeMint](enum.TokenInstruction.html) instruction
+    InitializeMint = 0,
+    /// A [InitializeAccount](enum.TokenInstruction.html) instruction
+    InitializeAccount = 1,
+    /// A [InitializeMultisig](enum.TokenInstruction.html) instruction
+    InitializeMultisig = 2,
+    /// A [Transfer](enum.TokenInstruction.html) instruction
+    Transfer = 3,
+    /// A [Approve](enum.TokenInstruction.html) instruction
+    Approve = 4,
+    /// A [Revoke](enum.TokenInstruction.html) instruction
+    Revoke = 5,
+    /// A [SetAuthority](enum.TokenInstruction.html) instruction
+    SetAuthority = 6,
+    /// A [MintTo](enum.TokenInstruction.html) instruction
+    MintTo = 7,
+    /// A [Burn](enum.TokenInstruction.html) instruction
+    Burn = 8,
+    /// A [CloseAccount](enum.TokenInstruction.html) instruction
+    CloseAccount = 9,
+    /// A [FreezeAccount](enum.TokenInstruction.html) instruction
+    FreezeAccount = 10,
+    /// A [ThawAccount](enum.TokenInstruction.html) instruction
+    ThawAccount = 11,
+    /// A [TransferChecked](enum.TokenInstruction.html) instruction
+    TransferChecked = 12,
+    /// An [ApproveChecked](enum.TokenInstruction.html) instruction
+    ApproveChecked = 13,
+    /// A [MintToChecked](enum.TokenInstruction.html) instruction
+    MintToChecked = 14,
+    /// A [BurnChecked](enum.TokenInstruction.html) instruction
+    BurnChecked = 15,
+    /// A [InitializeAccount2](enum.TokenInstruction.html) instruction
+    InitializeAccount2 = 16,
+    /// A [SyncNative](enum.TokenInstruction.html) instruction
+    SyncNative = 17,
+    /// A [InitializeAccount3](enum.TokenInstruction.html) instruction
+    InitializeAccount3 = 18,
+    /// A [InitializeMultisig2](enum.TokenInstruction.html) instruction
+    InitializeMultisig2 = 19,
+    /// A [InitializeMint2](enum.TokenInstruction.html) instruction
+    InitializeMint2 = 20,
+    /// A [GetAccountDataSize](enum.TokenInstruction.html) instruction
+    GetAccountDataSize = 21,
+    /// An [InitializeImmutableOwner](enum.TokenInstruction.html) instruction
+    InitializeImmutableOwner = 22,
+    /// An [AmountToUiAmount](enum.TokenInstruction.html) instruction
+    AmountToUiAmount = 23,
+    /// A [UiAmountToAmount](enum.TokenInstruction.html) instruction
+    UiAmountToAmount = 24,
+    /// A [InitializeBurnedTotal](enum.TokenInstruction.html) instruction
+    InitializeBurnedTotal = 100,
+    /// A [GetMintInfo](enum.TokenInstruction.html) instruction
+    GetMintInfo = 101,
+    /// A [SetPause](enum.TokenInstruction.html) instruction
+    SetPause = 102,
+    /// A [InitializePausableConfig](enum.TokenInstruction.html) instruction
+    InitializePausableConfig = 103,
+    /// A [WithdrawExcessLamports](enum.TokenInstruction.html) instruction
+    WithdrawExcessLamports = 104,
+    /// A [InitializeCreatedSlot](enum.TokenInstruction.html) instruction
+    InitializeCreatedSlot = 105,
+    /// A [BurnAndClose](enum.TokenInstruction.html) instruction
+    BurnAndClose = 106,
+    /// A [GetAccountBalance](enum.TokenInstruction.html) instruction
+    GetAccountBalance = 107,
+    /// A [GetAccountState](enum.TokenInstruction.html) instruction
+    GetAccountState = 108,
+    /// A [TransferCheckedWithFee](enum.TokenInstruction.html) instruction
+    TransferCheckedWithFee = 109,
+    /// A [InitializeTransferFeeConfig](enum.TokenInstruction.html) instruction
+    InitializeTransferFeeConfig = 110,
+    /// A [InitializeInterestBearingConfig](enum.TokenInstruction.html) instruction
+    InitializeInterestBearingConfig = 111,
+    /// A [InitializeMintWithSupply](enum.TokenInstruction.html) instruction
+    InitializeMintWithSupply = 112,
+}
+
+/// Reads only the tag byte of instruction data, without decoding the payload
+pub fn decode_instruction_type(data: &[u8]) -> Result<InstructionType, ProgramError> {
+    data.first()
+        .and_then(|&tag| InstructionType::try_from_primitive(tag).ok())
+        .ok_or_else(|| TokenError::InvalidInstruction.into())
+}
+
+/// Checks whether `instruction` targets this program and carries a decodable
+/// [TokenInstruction](enum.TokenInstruction.html).
+pub fn is_token_instruction(instruction: &Instruction) -> bool {
+    instruction.program_id == id() && TokenInstruction::unpack(&instruction.data).is_ok()
+}
+
+/// Creates a `InitializeMint` instruction.
+pub fn initialize_mint(
     token_program_id: &Pubkey,
     mint_pubkey: &Pubkey,
     mint_authority_pubkey: &Pubkey,
@@ -285,13 +1075,54 @@ pub fn initialize_mint(
     let data = TokenInstruction::InitializeMint {
         mint_authority: *mint_authority_pubkey,
         freeze_authority,
-        decimals
+        decimals,
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*mint_pubkey, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `InitializeMintWithSupply` instruction, minting `initial_supply`
+/// tokens to `recipient_pubkey`. An uninitialized recipient is initialized for
+/// the mint and owned by `recipient_owner_pubkey`.
+///
+/// The accounts are, in order: the mint, the rent sysvar, the recipient and the
+/// recipient's owner, as listed for `InitializeMintWithSupply`.
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_mint_with_supply(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    mint_authority_pubkey: &Pubkey,
+    freeze_authority_pubkey: Option<&Pubkey>,
+    decimals: u8,
+    recipient_pubkey: &Pubkey,
+    recipient_owner_pubkey: &Pubkey,
+    initial_supply: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let freeze_authority = freeze_authority_pubkey.cloned().into();
+    let data = TokenInstruction::InitializeMintWithSupply {
+        mint_authority: *mint_authority_pubkey,
+        freeze_authority,
+        decimals,
+        initial_supply,
     }
     .pack();
 
     let accounts = vec![
         AccountMeta::new(*mint_pubkey, false),
         AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new(*recipient_pubkey, false),
+        AccountMeta::new_readonly(*recipient_owner_pubkey, false),
     ];
 
     Ok(Instruction {
@@ -301,31 +1132,31 @@ pub fn initialize_mint(
     })
 }
 
-// /// Creates a `InitializeMint2` instruction.
-// pub fn initialize_mint2(
-//     token_program_id: &Pubkey,
-//     mint_pubkey: &Pubkey,
-//     mint_authority_pubkey: &Pubkey,
-//     freeze_authority_pubkey: Option<&Pubkey>,
-//     decimals:u8,
-// ) -> Result<Instruction, ProgramError> {
-//     check_program_account(token_program_id)?;
-//     let freeze_authority= freeze_authority_pubkey.cloned().into();
-//     let data = TokenInstruction::InitializeMint2 {
-//         mint_authority: *mint_authority_pubkey,
-//         freeze_authority,
-//         decimals,
-//     }
-//     .pack();
-
-//     let accounts = vec![AccountMeta::new(*mint_pubkey, false)];
-
-//     Ok(Instruction{
-//         program_id: *token_program_id,
-//         accounts,
-//         data,
-//     })
-// }
+/// Creates a `InitializeMint2` instruction.
+pub fn initialize_mint2(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    mint_authority_pubkey: &Pubkey,
+    freeze_authority_pubkey: Option<&Pubkey>,
+    decimals: u8,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let freeze_authority = freeze_authority_pubkey.cloned().into();
+    let data = TokenInstruction::InitializeMint2 {
+        mint_authority: *mint_authority_pubkey,
+        freeze_authority,
+        decimals,
+    }
+    .pack();
+
+    let accounts = vec![AccountMeta::new(*mint_pubkey, false)];
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
 
 /// Creates a `InitializeAccount` instruction.
 pub fn initialize_account(
@@ -352,8 +1183,122 @@ pub fn initialize_account(
 }
 
 
+/// Creates a `InitializeAccount2` instruction.
+pub fn initialize_account2(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::InitializeAccount2 {
+        owner: *owner_pubkey,
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*account_pubkey, false),
+        AccountMeta::new_readonly(*mint_pubkey, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `InitializeAccount3` instruction.
+pub fn initialize_account3(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::InitializeAccount3 {
+        owner: *owner_pubkey,
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*account_pubkey, false),
+        AccountMeta::new_readonly(*mint_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `InitializeMultisig` instruction.
+///
+/// Fails with `InvalidArgument` unless `1 <= m <= N <= MAX_SIGNERS`, where N
+/// is the number of signers.
+pub fn initialize_multisig(
+    token_program_id: &Pubkey,
+    multisig_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    m: u8,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    check_multisig_counts(signer_pubkeys.len(), m)?;
+    let data = TokenInstruction::InitializeMultisig { m }.pack();
+
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*multisig_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(sysvar::rent::id(), false));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `InitializeMultisig2` instruction.
+///
+/// Fails with `InvalidArgument` unless `1 <= m <= N <= MAX_SIGNERS`, where N
+/// is the number of signers.
+pub fn initialize_multisig2(
+    token_program_id: &Pubkey,
+    multisig_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    m: u8,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    check_multisig_counts(signer_pubkeys.len(), m)?;
+    let data = TokenInstruction::InitializeMultisig2 { m }.pack();
+
+    let mut accounts = Vec::with_capacity(1 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*multisig_pubkey, false));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Checks the signer counts the multisig initialize instructions accept
+fn check_multisig_counts(n: usize, m: u8) -> Result<(), ProgramError> {
+    if !(MID_SIGNERS..=MAX_SIGNERS).contains(&n) || !(MID_SIGNERS..=n).contains(&(m as usize)) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
 /// Creates a `Transfer` instruction.
-pub fn tranfer(
+pub fn transfer(
     token_program_id: &Pubkey,
     source_pubkey: &Pubkey,
     destination_pubkey: &Pubkey,
@@ -377,4 +1322,817 @@ pub fn tranfer(
         accounts,
         data
     })
-}
\ No newline at end of file
+}
+
+/// Creates a `Transfer` instruction.
+#[deprecated(since = "0.1.0", note = "Please use `transfer` instead")]
+pub fn tranfer(
+    token_program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    transfer(
+        token_program_id,
+        source_pubkey,
+        destination_pubkey,
+        authority_pubkey,
+        signer_pubkeys,
+        amount,
+    )
+}
+
+/// Creates an `Approve` instruction.
+pub fn approve(
+    token_program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    delegate_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::Approve { amount }.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*source_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*delegate_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*owner_pubkey, signer_pubkeys.is_empty()));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `Revoke` instruction.
+pub fn revoke(
+    token_program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::Revoke.pack();
+
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*source_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*owner_pubkey, signer_pubkeys.is_empty()));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetAuthority` instruction. Pass `None` as the new authority to
+/// disable minting or freezing permanently.
+pub fn set_authority(
+    token_program_id: &Pubkey,
+    owned_pubkey: &Pubkey,
+    new_authority_pubkey: Option<&Pubkey>,
+    authority_type: AuthorityType,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let new_authority = new_authority_pubkey.cloned().into();
+    let data = TokenInstruction::SetAuthority {
+        authority_type,
+        new_authority,
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*owned_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*owner_pubkey, signer_pubkeys.is_empty()));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `MintTo` instruction.
+pub fn mint_to(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    account_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::MintTo { amount }.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*mint_pubkey, false));
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*owner_pubkey, signer_pubkeys.is_empty()));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `Burn` instruction.
+pub fn burn(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::Burn { amount }.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*authority_pubkey, signer_pubkeys.is_empty()));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CloseAccount` instruction.
+pub fn close_account(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::CloseAccount.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new(*destination_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*owner_pubkey, signer_pubkeys.is_empty()));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `InitializeBurnedTotal` instruction.
+pub fn initialize_burned_total(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts: vec![AccountMeta::new(*mint_pubkey, false)],
+        data: TokenInstruction::InitializeBurnedTotal.pack(),
+    })
+}
+
+/// Creates an `InitializeCreatedSlot` instruction.
+pub fn initialize_created_slot(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts: vec![AccountMeta::new(*mint_pubkey, false)],
+        data: TokenInstruction::InitializeCreatedSlot.pack(),
+    })
+}
+
+/// Creates a `GetMintInfo` instruction.
+pub fn get_mint_info(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts: vec![AccountMeta::new_readonly(*mint_pubkey, false)],
+        data: TokenInstruction::GetMintInfo.pack(),
+    })
+}
+
+/// Creates a `Transfer` instruction, rejecting a source equal to the destination.
+///
+/// Self-transfers are valid on-chain no-ops but are almost always a client bug.
+pub fn transfer_distinct(
+    token_program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    if source_pubkey == destination_pubkey {
+        return Err(ProgramError::InvalidArgument);
+    }
+    transfer(
+        token_program_id,
+        source_pubkey,
+        destination_pubkey,
+        authority_pubkey,
+        signer_pubkeys,
+        amount,
+    )
+}
+
+/// Creates the instructions that initialize `mint_pubkey`, initialize
+/// `account_pubkey` for it, and mint `amount` tokens into that account, in that
+/// order. Both accounts must already be allocated and rent-exempt.
+///
+/// Fails with `InvalidArgument` if `amount` is zero or `decimals` exceeds
+/// `MAX_DECIMALS`.
+pub fn create_and_fund_mint(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    mint_authority_pubkey: &Pubkey,
+    account_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    decimals: u8,
+    amount: u64,
+) -> Result<Vec<Instruction>, ProgramError> {
+    if amount == 0 || decimals > MAX_DECIMALS {
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(vec![
+        initialize_mint(
+            token_program_id,
+            mint_pubkey,
+            mint_authority_pubkey,
+            None,
+            decimals,
+        )?,
+        initialize_account(token_program_id, account_pubkey, mint_pubkey, owner_pubkey)?,
+        mint_to(
+            token_program_id,
+            mint_pubkey,
+            account_pubkey,
+            mint_authority_pubkey,
+            &[],
+            amount,
+        )?,
+    ])
+}
+
+/// Derives the canonical token account address of `wallet` for `mint`, as the
+/// program address of `token_program_id` with the seeds `[wallet,
+/// token_program_id, mint]`. Returns the address and its bump seed.
+pub fn find_token_account_address(
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    token_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), token_program_id.as_ref(), mint.as_ref()],
+        token_program_id,
+    )
+}
+
+/// Checks offline whether `authority` could transfer `amount` out of `source`.
+///
+/// This mirrors the checks performed by the on-chain `Transfer` processor so that
+/// clients can fail early, but it is advisory only: the account may change before
+/// the transaction lands.
+pub fn validate_transfer(
+    source: &Account,
+    amount: u64,
+    authority: &Pubkey,
+) -> Result<(), TokenError> {
+    if source.is_frozen() {
+        return Err(TokenError::AccountFrozen);
+    }
+    if source.amount < amount {
+        return Err(TokenError::InsufficientFunds);
+    }
+    match source.spendable_amount(authority) {
+        Some(spendable) if spendable < amount => Err(TokenError::InsufficientFunds),
+        Some(_) => Ok(()),
+        None => Err(TokenError::OwnerMismatch),
+    }
+}
+
+/// Creates a `TransferChecked` instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_checked(
+    token_program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+    decimals: u8,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::TransferChecked { amount, decimals }.pack();
+
+    let mut accounts = Vec::with_capacity(4 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*source_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
+    accounts.push(AccountMeta::new(*destination_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *authority_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates one `TransferChecked` instruction from `source_pubkey` to each of
+/// `destinations`, in the order given.
+///
+/// Fails with `InvalidArgument` if `destinations` is empty or any amount is zero.
+pub fn transfer_checked_many(
+    token_program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    destinations: &[(&Pubkey, u64)],
+    authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    decimals: u8,
+) -> Result<Vec<Instruction>, ProgramError> {
+    if destinations.is_empty() || destinations.iter().any(|(_, amount)| *amount == 0) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    destinations
+        .iter()
+        .map(|(destination_pubkey, amount)| {
+            transfer_checked(
+                token_program_id,
+                source_pubkey,
+                mint_pubkey,
+                destination_pubkey,
+                authority_pubkey,
+                signer_pubkeys,
+                *amount,
+                decimals,
+            )
+        })
+        .collect()
+}
+
+/// Creates an `ApproveChecked` instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn approve_checked(
+    token_program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    delegate_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+    decimals: u8,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::ApproveChecked { amount, decimals }.pack();
+
+    let mut accounts = Vec::with_capacity(4 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*source_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*delegate_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*owner_pubkey, signer_pubkeys.is_empty()));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `MintToChecked` instruction.
+pub fn mint_to_checked(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    account_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+    decimals: u8,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::MintToChecked { amount, decimals }.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*mint_pubkey, false));
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*owner_pubkey, signer_pubkeys.is_empty()));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `BurnChecked` instruction.
+pub fn burn_checked(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+    decimals: u8,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::BurnChecked { amount, decimals }.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*authority_pubkey, signer_pubkeys.is_empty()));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `FreezeAccount` instruction.
+pub fn freeze_account(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    freeze_authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::FreezeAccount.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*freeze_authority_pubkey, signer_pubkeys.is_empty()));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `ThawAccount` instruction.
+pub fn thaw_account(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    freeze_authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::ThawAccount.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*freeze_authority_pubkey, signer_pubkeys.is_empty()));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SyncNative` instruction.
+pub fn sync_native(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts: vec![AccountMeta::new(*account_pubkey, false)],
+        data: TokenInstruction::SyncNative.pack(),
+    })
+}
+
+/// Creates one `SyncNative` instruction per native account, in the order given.
+pub fn sync_native_many(
+    token_program_id: &Pubkey,
+    account_pubkeys: &[Pubkey],
+) -> Result<Vec<Instruction>, ProgramError> {
+    account_pubkeys
+        .iter()
+        .map(|account_pubkey| sync_native(token_program_id, account_pubkey))
+        .collect()
+}
+
+/// Creates a `GetAccountDataSize` instruction.
+pub fn get_account_data_size(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts: vec![AccountMeta::new_readonly(*mint_pubkey, false)],
+        data: TokenInstruction::GetAccountDataSize.pack(),
+    })
+}
+
+/// Creates an `InitializeImmutableOwner` instruction.
+pub fn initialize_immutable_owner(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts: vec![AccountMeta::new(*account_pubkey, false)],
+        data: TokenInstruction::InitializeImmutableOwner.pack(),
+    })
+}
+
+/// Creates an `AmountToUiAmount` instruction.
+pub fn amount_to_ui_amount(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts: vec![AccountMeta::new_readonly(*mint_pubkey, false)],
+        data: TokenInstruction::AmountToUiAmount { amount }.pack(),
+    })
+}
+
+/// Creates a `UiAmountToAmount` instruction.
+pub fn ui_amount_to_amount(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    ui_amount: &str,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts: vec![AccountMeta::new_readonly(*mint_pubkey, false)],
+        data: TokenInstruction::UiAmountToAmount { ui_amount }.pack(),
+    })
+}
+
+/// Creates a `SetPause` instruction.
+pub fn set_pause(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    pause_authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    paused: bool,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::SetPause { paused }.pack();
+
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*pause_authority_pubkey, signer_pubkeys.is_empty()));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `InitializePausableConfig` instruction.
+pub fn initialize_pausable_config(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    pause_authority_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts: vec![AccountMeta::new(*mint_pubkey, false)],
+        data: TokenInstruction::InitializePausableConfig {
+            pause_authority: *pause_authority_pubkey,
+        }
+        .pack(),
+    })
+}
+
+/// Creates a `WithdrawExcessLamports` instruction.
+pub fn withdraw_excess_lamports(
+    token_program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::WithdrawExcessLamports.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*source_pubkey, false));
+    accounts.push(AccountMeta::new(*destination_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*owner_pubkey, signer_pubkeys.is_empty()));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `BurnAndClose` instruction.
+pub fn burn_and_close(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::BurnAndClose { amount }.pack();
+
+    let mut accounts = Vec::with_capacity(4 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new(*mint_pubkey, false));
+    accounts.push(AccountMeta::new(*destination_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*authority_pubkey, signer_pubkeys.is_empty()));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `GetAccountBalance` instruction.
+pub fn get_account_balance(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts: vec![AccountMeta::new_readonly(*account_pubkey, false)],
+        data: TokenInstruction::GetAccountBalance.pack(),
+    })
+}
+
+/// Creates a `GetAccountState` instruction.
+pub fn get_account_state(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts: vec![AccountMeta::new_readonly(*account_pubkey, false)],
+        data: TokenInstruction::GetAccountState.pack(),
+    })
+}
+
+/// Creates a `TransferCheckedWithFee` instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_checked_with_fee(
+    token_program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    fee_collector_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+    decimals: u8,
+    fee: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::TransferCheckedWithFee {
+        amount,
+        decimals,
+        fee,
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(5 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*source_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
+    accounts.push(AccountMeta::new(*destination_pubkey, false));
+    accounts.push(AccountMeta::new(*fee_collector_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*authority_pubkey, signer_pubkeys.is_empty()));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `InitializeTransferFeeConfig` instruction.
+pub fn initialize_transfer_fee_config(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    transfer_fee_basis_points: u16,
+    maximum_fee: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts: vec![AccountMeta::new(*mint_pubkey, false)],
+        data: TokenInstruction::InitializeTransferFeeConfig {
+            transfer_fee_basis_points,
+            maximum_fee,
+        }
+        .pack(),
+    })
+}
+
+/// Creates an `InitializeInterestBearingConfig` instruction.
+pub fn initialize_interest_bearing_config(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    rate: i16,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts: vec![AccountMeta::new(*mint_pubkey, false)],
+        data: TokenInstruction::InitializeInterestBearingConfig { rate }.pack(),
+    })
+}
@@ -4,7 +4,9 @@
 //! An ERC20-like Token program for Solana blockchain
 
 pub mod error;
+pub mod extension;
 pub mod instruction;
+pub mod native_mint;
 pub mod processor;
 pub mod state;
 
@@ -13,14 +15,68 @@ mod entrypoint;
 
 // Export current sdk types for downstream users building with a different sdk version
 pub use solana_program;
+use error::TokenError;
 use solana_program::{entrypoint::ProgramResult, program_error::ProgramError, pubkey::Pubkey};
 
 solana_program::declare_id!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
 
+/// Returns whether `spl_token_program_id` is the id of this token program
+pub fn is_valid_program_id(spl_token_program_id: &Pubkey) -> bool {
+    spl_token_program_id == &id()
+}
+
 /// Checks that the supplied program ID is the correct one for SPL-token
 pub fn check_program_account(spl_token_program_id: &Pubkey) -> ProgramResult {
-    if spl_token_program_id != &id() {
+    if !is_valid_program_id(spl_token_program_id) {
         return Err(ProgramError::IncorrectProgramId);
     }
     Ok(())
 }
+
+/// Converts a raw amount of tokens to a decimal string, trimming trailing zeros
+/// and a trailing decimal point
+pub fn amount_to_ui_amount_string_trimmed(amount: u64, decimals: u8) -> String {
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return amount.to_string();
+    }
+    let mut ui_amount = format!("{:0width$}", amount, width = decimals + 1);
+    ui_amount.insert(ui_amount.len() - decimals, '.');
+    ui_amount
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+/// Parses a decimal string into a raw amount of tokens. Fails with
+/// `InvalidArgument` on malformed input or more fractional digits than
+/// `decimals`, and with `Overflow` if the amount does not fit into a `u64`.
+pub fn try_ui_amount_into_amount(ui_amount: &str, decimals: u8) -> Result<u64, ProgramError> {
+    let decimals = decimals as usize;
+    let mut parts = ui_amount.split('.');
+    // `split` always yields at least one part
+    let integer_part = parts.next().unwrap_or("");
+    let fraction_part = parts.next().unwrap_or("").trim_end_matches('0');
+    if parts.next().is_some()
+        || (integer_part.is_empty() && fraction_part.is_empty())
+        || fraction_part.len() > decimals
+        || !integer_part
+            .bytes()
+            .chain(fraction_part.bytes())
+            .all(|digit| digit.is_ascii_digit())
+    {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let padding = (fraction_part.len()..decimals).map(|_| b'0');
+    integer_part
+        .bytes()
+        .chain(fraction_part.bytes())
+        .chain(padding)
+        .try_fold(0u64, |amount, digit| {
+            amount
+                .checked_mul(10)
+                .and_then(|amount| amount.checked_add(u64::from(digit - b'0')))
+        })
+        .ok_or_else(|| TokenError::Overflow.into())
+}
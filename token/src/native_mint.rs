@@ -0,0 +1,12 @@
+//! The Mint that represents the native token
+
+/// There are 10^9 lamports in one SOL
+pub const DECIMALS: u8 = 9;
+
+// The Mint for native SOL Token accounts
+solana_program::declare_id!("So11111111111111111111111111111111111111112");
+
+/// Returns whether `mint` is the native mint, whose token accounts wrap SOL
+pub fn is_native_mint(mint: &solana_program::pubkey::Pubkey) -> bool {
+    check_id(mint)
+}
@@ -1,16 +1,32 @@
 //! Program state processor
 
-use std::{borrow::{Borrow, BorrowMut}, cmp::min};
-
 use crate::{
     error::TokenError,
-    instruction::{AuthorityType, TokenInstruction, MAX_SIGNERS},
-    state::{Account, Mint, Multisig},
+    extension::{
+        add_extension, get_extension, get_extension_bytes, get_extension_types, get_u64_extension,
+        init_extension, set_extension, set_u64_extension, split_extensions, split_extensions_mut,
+        ExtensionType, FeeRounding, InterestBearingConfig, PausableConfig, TransferFee,
+        ONE_IN_BASIS_POINTS,
+    },
+    instruction::{
+        decode_instruction_type, AuthorityType, TokenInstruction, MAX_SIGNERS, MID_SIGNERS,
+    },
+    state::{Account, AccountState, Mint, Multisig, ACCOUNT_VERSION, MAX_DECIMALS},
 };
 
 use num_traits::FromPrimitive;
-use solana_program::{account_info::{self, AccountInfo, next_account_info}, decode_error::DecodeError, entrypoint::ProgramResult, entrypoint_deprecated::ProgramResult, msg, program_error::{PrintProgramError, ProgramError}, program_option::COption, program_pack::{IsInitialized, Pack}, pubkey::{self, Pubkey}, sysvar::{rent::Rent, Sysvar}};
-use solana_sdk::account::accounts_equal;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    decode_error::DecodeError,
+    entrypoint::ProgramResult,
+    msg,
+    program::set_return_data,
+    program_error::{PrintProgramError, ProgramError},
+    program_option::COption,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    sysvar::{self, clock::Clock, rent::Rent, Sysvar},
+};
 
 /// Program state handler
 pub struct Processor {}
@@ -20,8 +36,171 @@ impl Processor {
         decimals: u8,
         mint_authority: Pubkey,
         freeze_authority: COption<Pubkey>,
+        initial_supply: Option<u64>,
         rent_sysvar_account: bool,
     ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let mint_info = next_account_info(account_info_iter)?;
+        let mint_data_len = mint_info.data_len();
+        let rent = Self::get_rent(account_info_iter, rent_sysvar_account)?;
+        let recipient = if let Some(initial_supply) = initial_supply {
+            let recipient_info = next_account_info(account_info_iter)?;
+            let recipient_owner_info = next_account_info(account_info_iter)?;
+            Self::check_account_aliasing(mint_info, recipient_info)?;
+            Some((recipient_info, recipient_owner_info, initial_supply))
+        } else {
+            None
+        };
+
+        let mut mint = Mint::unpack_unchecked(Self::split_mint(&mint_info.data.borrow())?.0)?;
+        if mint.is_initialized {
+            return Err(TokenError::AlreadyInUse.into());
+        }
+
+        if !rent.is_exempt(mint_info.lamports(), mint_data_len) {
+            return Err(TokenError::NotRentExempt.into());
+        }
+        if decimals > MAX_DECIMALS {
+            return Err(TokenError::InvalidMintDecimals.into());
+        }
+
+        mint.mint_authority = COption::Some(mint_authority);
+        mint.decimals = decimals;
+        mint.is_initialized = true;
+        mint.freeze_authority = freeze_authority;
+        mint.supply = initial_supply.unwrap_or(0);
+
+        let mut mint_data = mint_info.data.borrow_mut();
+        let (mint_base, mint_extensions) = Self::split_mint_mut(&mut mint_data)?;
+        // The creation slot is only recorded for mints set up with InitializeCreatedSlot
+        if get_u64_extension(mint_extensions, ExtensionType::CreatedSlot)?.is_some() {
+            set_u64_extension(
+                mint_extensions,
+                ExtensionType::CreatedSlot,
+                Clock::get()?.slot,
+            )?;
+        }
+        Mint::pack(mint, mint_base)?;
+        drop(mint_data);
+
+        // The initial supply is minted in the same instruction, so the mint is never
+        // observable with an empty supply. An uninitialized recipient is initialized
+        // here, once the mint is; an initialized one has to belong to the mint.
+        if let Some((recipient_info, recipient_owner_info, initial_supply)) = recipient {
+            let recipient_initialized =
+                Account::unpack_unchecked(Self::split_account(&recipient_info.data.borrow())?.0)?
+                    .is_initialized();
+            if recipient_initialized {
+                let recipient = Self::unpack_account(&recipient_info.data.borrow())?;
+                if recipient.mint != *mint_info.key {
+                    return Err(TokenError::MintMismatch.into());
+                }
+            } else {
+                Self::initialize_account_state(
+                    recipient_info,
+                    mint_info,
+                    recipient_owner_info.key,
+                    &rent,
+                )?;
+            }
+            let mut recipient_data = recipient_info.data.borrow_mut();
+            let recipient_base = Self::split_account_mut(&mut recipient_data)?.0;
+            let mut recipient = Account::unpack(recipient_base)?;
+            recipient.amount = recipient
+                .amount
+                .checked_add(initial_supply)
+                .ok_or(TokenError::Overflow)?;
+            Account::pack(recipient, recipient_base)?;
+        }
+        Ok(())
+    }
+
+    /// Initializes the token account state of `new_account_info` for the mint of
+    /// `mint_info`, once the account data, the mint and the rent check out
+    fn initialize_account_state(
+        new_account_info: &AccountInfo,
+        mint_info: &AccountInfo,
+        owner: &Pubkey,
+        rent: &Rent,
+    ) -> ProgramResult {
+        let new_account_info_data_len = new_account_info.data_len();
+
+        let mut account =
+            Account::unpack_unchecked(Self::split_account(&new_account_info.data.borrow())?.0)?;
+        if account.is_initialized() {
+            return Err(TokenError::AlreadyInUse.into());
+        }
+
+        if !rent.is_exempt(new_account_info.lamports(), new_account_info_data_len) {
+            return Err(TokenError::NotRentExempt.into());
+        }
+
+        let is_native_mint = crate::native_mint::is_native_mint(mint_info.key);
+        let required_extensions = if is_native_mint {
+            vec![]
+        } else {
+            let mint_data = mint_info.data.borrow();
+            let (mint_base, mint_extensions) = Self::split_mint(&mint_data)?;
+            Mint::unpack(mint_base)
+                .map_err(|_| Into::<ProgramError>::into(TokenError::InvalidMint))?;
+            ExtensionType::get_required_account_extensions(&get_extension_types(mint_extensions)?)
+        };
+
+        // Extensions follow the base state and its version byte, and may have been
+        // added before initialization
+        let mut extension_types =
+            get_extension_types(Self::account_extensions(&new_account_info.data.borrow()))?;
+        let missing_extensions = required_extensions
+            .into_iter()
+            .filter(|extension_type| !extension_types.contains(extension_type))
+            .collect::<Vec<_>>();
+        extension_types.extend(&missing_extensions);
+        if extension_types.is_empty() {
+            if new_account_info_data_len != Account::LEN {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        } else {
+            let required_len = ExtensionType::get_account_len(Account::LEN + 1, &extension_types);
+            if new_account_info_data_len < required_len {
+                msg!(
+                    "Error: the mint requires a token account of at least {} bytes",
+                    required_len
+                );
+                return Err(ProgramError::AccountDataTooSmall);
+            }
+        }
+
+        account.mint = *mint_info.key;
+        account.owner = *owner;
+        account.delegate = COption::None;
+        account.delegated_amount = 0;
+        account.state = AccountState::Initialized;
+        account.close_authority = COption::None;
+        if is_native_mint {
+            let rent_exempt_reserve = rent.minimum_balance(new_account_info_data_len);
+            account.is_native = COption::Some(rent_exempt_reserve);
+            account.amount = new_account_info
+                .lamports()
+                .checked_sub(rent_exempt_reserve)
+                .ok_or(TokenError::Overflow)?;
+        } else {
+            account.is_native = COption::None;
+            account.amount = 0;
+        };
+
+        let mut account_data = new_account_info.data.borrow_mut();
+        let (account_base, account_extensions) = Self::split_account_mut(&mut account_data)?;
+        Account::pack(account, account_base)?;
+        if let Some((version, account_extensions)) = account_extensions.split_first_mut() {
+            *version = ACCOUNT_VERSION;
+            for extension_type in missing_extensions {
+                init_extension(
+                    account_extensions,
+                    extension_type,
+                    extension_type.value_len(),
+                )?;
+            }
+        }
         Ok(())
     }
 
@@ -32,7 +211,32 @@ impl Processor {
         mint_authority: Pubkey,
         freeze_authority: COption<Pubkey>,
     ) -> ProgramResult {
-        Self::_process_initialize_mint(accounts, decimals, mint_authority, freeze_authority, true)
+        Self::_process_initialize_mint(
+            accounts,
+            decimals,
+            mint_authority,
+            freeze_authority,
+            None,
+            true,
+        )
+    }
+
+    /// Processes an [InitializeMintWithSupply](enum.TokenInstruction.html) instruction.
+    pub fn process_initialize_mint_with_supply(
+        accounts: &[AccountInfo],
+        decimals: u8,
+        mint_authority: Pubkey,
+        freeze_authority: COption<Pubkey>,
+        initial_supply: u64,
+    ) -> ProgramResult {
+        Self::_process_initialize_mint(
+            accounts,
+            decimals,
+            mint_authority,
+            freeze_authority,
+            Some(initial_supply),
+            true,
+        )
     }
 
     /// Processes an [InitializeMint2](enum.TokenInstruction.html) instruction.
@@ -42,7 +246,14 @@ impl Processor {
         mint_authority: Pubkey,
         freeze_authority: COption<Pubkey>,
     ) -> ProgramResult {
-        Self::_process_initialize_mint(accounts, decimals, mint_authority, freeze_authority, false)
+        Self::_process_initialize_mint(
+            accounts,
+            decimals,
+            mint_authority,
+            freeze_authority,
+            None,
+            false,
+        )
     }
 
     fn _process_initialize_account(
@@ -50,7 +261,16 @@ impl Processor {
         owner: Option<&Pubkey>,
         rent_sysvar_account: bool,
     ) -> ProgramResult {
-        Ok(())
+        let account_info_iter = &mut accounts.iter();
+        let new_account_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let owner = if let Some(owner) = owner {
+            owner
+        } else {
+            next_account_info(account_info_iter)?.key
+        };
+        let rent = Self::get_rent(account_info_iter, rent_sysvar_account)?;
+        Self::initialize_account_state(new_account_info, mint_info, owner, &rent)
     }
 
     /// Processes an [InitializeAccount](enum.TokenInstruction.htmml) instruction.
@@ -73,6 +293,55 @@ impl Processor {
         m: u8,
         rent_sysvar_account: bool,
     ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let multisig_info = next_account_info(account_info_iter)?;
+        let multisig_info_data_len = multisig_info.data_len();
+        let rent = Self::get_rent(account_info_iter, rent_sysvar_account)?;
+
+        let mut multisig = Multisig::unpack_unchecked(&multisig_info.data.borrow())?;
+        if multisig.is_initialized {
+            return Err(TokenError::AlreadyInUse.into());
+        }
+
+        if !rent.is_exempt(multisig_info.lamports(), multisig_info_data_len) {
+            return Err(TokenError::NotRentExempt.into());
+        }
+
+        let signer_infos = account_info_iter.as_slice();
+        let n = signer_infos.len();
+        if !(MID_SIGNERS..=MAX_SIGNERS).contains(&n) {
+            return Err(TokenError::InvalidNumberOfProvidedSigners.into());
+        }
+        if !(MID_SIGNERS..=n).contains(&(m as usize)) {
+            return Err(TokenError::InvalidNumberOfRequiredSigners.into());
+        }
+
+        multisig.m = m;
+        multisig.n = n as u8;
+        for (signer, signer_info) in multisig.signers.iter_mut().zip(signer_infos) {
+            *signer = *signer_info.key;
+        }
+        multisig.is_initialized = true;
+
+        Multisig::pack(multisig, &mut multisig_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes an [InitializeImmutableOwner](enum.TokenInstruction.html) instruction.
+    pub fn process_initialize_immutable_owner(accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let account_info = next_account_info(account_info_iter)?;
+
+        let mut account_data = account_info.data.borrow_mut();
+        let (account_base, account_extensions) = Self::split_account_mut(&mut account_data)?;
+        if Account::unpack_unchecked(account_base)?.is_initialized() {
+            return Err(TokenError::AlreadyInUse.into());
+        }
+        let (version, account_extensions) = account_extensions
+            .split_first_mut()
+            .ok_or(ProgramError::AccountDataTooSmall)?;
+        *version = ACCOUNT_VERSION;
+        init_extension(account_extensions, ExtensionType::ImmutableOwner, 0)?;
         Ok(())
     }
 
@@ -87,6 +356,13 @@ impl Processor {
     }
 
     /// Processes a [Transfer](enum.TokenInstruction.html) instruction.
+    ///
+    /// A zero `amount` is accepted once the accounts and the authority's
+    /// signature check out, and leaves all state untouched.
+    ///
+    /// Unlike in SPL Token, the authority can't be the source account itself, so
+    /// a token account that owns itself has to hand its ownership to another
+    /// address with `SetAuthority` before its tokens can move.
     pub fn process_transfer(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
@@ -106,8 +382,14 @@ impl Processor {
         let dest_account_info = next_account_info(account_info_iter)?;
         let authority_info = next_account_info(account_info_iter)?;
 
-        let mut source_account = Account::unpack(&source_account_info.data.borrow())?;
-        let mut dest_account = Account::unpack(&dest_account_info.data.borrow())?;
+        if let Some((mint_info, _)) = expected_mint_info {
+            Self::check_account_aliasing(mint_info, source_account_info)?;
+            Self::check_account_aliasing(mint_info, dest_account_info)?;
+        }
+        Self::check_account_aliasing(source_account_info, authority_info)?;
+
+        let mut source_account = Self::unpack_account(&source_account_info.data.borrow())?;
+        let mut dest_account = Self::unpack_account(&dest_account_info.data.borrow())?;
 
         if source_account.is_frozen() || dest_account.is_frozen() {
             return Err(TokenError::AccountFrozen.into());
@@ -120,17 +402,57 @@ impl Processor {
             return Err(TokenError::MintMismatch.into());
         }
 
-        if let Some((mint_info, expected_decimals)) = expected_mint_info {
-            if source_account.mint != *mint_info.key {
-                return Err(TokenError::MintMismatch.into());
+        // The pause and the transfer fee live on the mint, which only the checked
+        // transfer supplies. Both accounts share the mint, but either may carry
+        // the marker.
+        if expected_mint_info.is_none() {
+            for account_info in [source_account_info, dest_account_info] {
+                let account_data = account_info.data.borrow();
+                let account_extensions = Self::account_extensions(&account_data);
+                for marker in [
+                    ExtensionType::PausableAccount,
+                    ExtensionType::TransferFeeAccount,
+                ] {
+                    if get_extension_bytes(account_extensions, marker)?.is_some() {
+                        return Err(TokenError::MintRequiredForTransfer.into());
+                    }
+                }
             }
+        }
 
-            let mint = Mint::unpack(&mint_info.data.borrow_mut())?;
-            if expected_decimals != mint.decimals {
-                return Err(TokenError::MintDecimalsMismatch.into());
+        if let Some((mint_info, expected_decimals)) = expected_mint_info {
+            // The native mint has fixed decimals, so only non-native mints are read
+            if source_account.is_native() {
+                if source_account.mint != *mint_info.key {
+                    return Err(TokenError::MintMismatch.into());
+                }
+                if expected_decimals != crate::native_mint::DECIMALS {
+                    return Err(TokenError::MintDecimalsMismatch.into());
+                }
+            } else {
+                let mint_data = mint_info.data.borrow();
+                let (mint_base, mint_extensions) = Self::split_mint(&mint_data)?;
+                Self::check_expected_decimals(
+                    &Mint::unpack(mint_base)?,
+                    Some(expected_decimals),
+                    mint_info.key,
+                    &source_account.mint,
+                )?;
+                let pausable =
+                    get_extension::<PausableConfig>(mint_extensions, ExtensionType::Pausable)?;
+                if pausable.is_some_and(|pausable| pausable.paused) {
+                    return Err(TokenError::MintPaused.into());
+                }
+                // The fee can only be paid through TransferCheckedWithFee
+                if get_extension_bytes(mint_extensions, ExtensionType::TransferFeeConfig)?.is_some()
+                {
+                    return Err(TokenError::FeeMismatch.into());
+                }
             }
         }
 
+        // A self-transfer is authorized and checked like any other transfer, but
+        // leaves the balance and the delegation untouched
         let self_transfer = source_account_info.key == dest_account_info.key;
 
         match source_account.delegate {
@@ -162,10 +484,16 @@ impl Processor {
             )?,
         };
 
-        if self_transfer {
+        // Like a self-transfer, a zero amount only needs to be authorized
+        if self_transfer || amount == 0 {
             return Ok(());
         }
 
+        if source_account.is_native() {
+            Self::check_native_backing(&source_account, source_account_info.lamports())?;
+            Self::check_native_backing(&dest_account, dest_account_info.lamports())?;
+        }
+
         source_account.amount = source_account
             .amount
             .checked_sub(amount)
@@ -187,13 +515,175 @@ impl Processor {
                 .ok_or(TokenError::Overflow)?;
         }
 
-        Account::pack(source_account, &mut source_account_info.data.borrow_mut())?;
-        Account::pack(dest_account, &mut dest_account_info.data.borrow_mut())?;
+        Account::pack(
+            source_account,
+            Self::split_account_mut(&mut source_account_info.data.borrow_mut())?.0,
+        )?;
+        Account::pack(
+            dest_account,
+            Self::split_account_mut(&mut dest_account_info.data.borrow_mut())?.0,
+        )?;
+
+        #[cfg(feature = "verbose-logs")]
+        msg!(
+            "Transferred {} from {} to {}",
+            amount,
+            Self::short_key(source_account_info.key),
+            Self::short_key(dest_account_info.key)
+        );
+
+        Ok(())
+    }
+
+    /// Processes a [TransferCheckedWithFee](enum.TokenInstruction.html) instruction.
+    ///
+    /// Unlike [process_transfer](#method.process_transfer), the source,
+    /// destination and fee collector must be three distinct non-native accounts.
+    pub fn process_transfer_checked_with_fee(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        decimals: u8,
+        fee: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let source_account_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let dest_account_info = next_account_info(account_info_iter)?;
+        let fee_account_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+
+        if fee > amount {
+            return Err(TokenError::InsufficientFunds.into());
+        }
+
+        for (a, b) in [
+            (source_account_info, dest_account_info),
+            (source_account_info, fee_account_info),
+            (dest_account_info, fee_account_info),
+            (source_account_info, authority_info),
+        ] {
+            Self::check_account_aliasing(a, b)?;
+        }
+        for account_info in [source_account_info, dest_account_info, fee_account_info] {
+            Self::check_account_aliasing(mint_info, account_info)?;
+        }
+
+        let mut source_account = Self::unpack_account(&source_account_info.data.borrow())?;
+        let mut dest_account = Self::unpack_account(&dest_account_info.data.borrow())?;
+        let mut fee_account = Self::unpack_account(&fee_account_info.data.borrow())?;
+
+        if source_account.is_frozen() || dest_account.is_frozen() || fee_account.is_frozen() {
+            return Err(TokenError::AccountFrozen.into());
+        }
+        if source_account.is_native() {
+            return Err(TokenError::NativeNotSupported.into());
+        }
+        if source_account.mint != *mint_info.key
+            || dest_account.mint != *mint_info.key
+            || fee_account.mint != *mint_info.key
+        {
+            return Err(TokenError::MintMismatch.into());
+        }
+        if source_account.amount < amount {
+            return Err(TokenError::InsufficientFunds.into());
+        }
+
+        {
+            let mint_data = mint_info.data.borrow();
+            let (mint_base, mint_extensions) = Self::split_mint(&mint_data)?;
+            let mint = Mint::unpack(mint_base)?;
+            Self::check_expected_decimals(
+                &mint,
+                Some(decimals),
+                mint_info.key,
+                &source_account.mint,
+            )?;
+            let pausable =
+                get_extension::<PausableConfig>(mint_extensions, ExtensionType::Pausable)?;
+            if pausable.is_some_and(|pausable| pausable.paused) {
+                return Err(TokenError::MintPaused.into());
+            }
+            let transfer_fee =
+                get_extension::<TransferFee>(mint_extensions, ExtensionType::TransferFeeConfig)?;
+            if let Some(transfer_fee) = transfer_fee {
+                if transfer_fee.calculate_fee(amount, FeeRounding::Up) != Some(fee) {
+                    return Err(TokenError::FeeMismatch.into());
+                }
+            }
+        }
+
+        match source_account.delegate {
+            COption::Some(ref delegate) if authority_info.key == delegate => {
+                Self::validate_owner(
+                    program_id,
+                    delegate,
+                    authority_info,
+                    account_info_iter.as_slice(),
+                )?;
+                source_account.delegated_amount = source_account
+                    .delegated_amount
+                    .checked_sub(amount)
+                    .ok_or(TokenError::InsufficientFunds)?;
+                if source_account.delegated_amount == 0 {
+                    source_account.delegate = COption::None;
+                }
+            }
+            _ => Self::validate_owner(
+                program_id,
+                &source_account.owner,
+                authority_info,
+                account_info_iter.as_slice(),
+            )?,
+        };
+
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let net_amount = amount
+            .checked_sub(fee)
+            .ok_or(TokenError::InsufficientFunds)?;
+        source_account.amount = source_account
+            .amount
+            .checked_sub(amount)
+            .ok_or(TokenError::InsufficientFunds)?;
+        dest_account.amount = dest_account
+            .amount
+            .checked_add(net_amount)
+            .ok_or(TokenError::Overflow)?;
+        fee_account.amount = fee_account
+            .amount
+            .checked_add(fee)
+            .ok_or(TokenError::Overflow)?;
+
+        Account::pack(
+            source_account,
+            Self::split_account_mut(&mut source_account_info.data.borrow_mut())?.0,
+        )?;
+        Account::pack(
+            dest_account,
+            Self::split_account_mut(&mut dest_account_info.data.borrow_mut())?.0,
+        )?;
+        Account::pack(
+            fee_account,
+            Self::split_account_mut(&mut fee_account_info.data.borrow_mut())?.0,
+        )?;
 
         Ok(())
     }
 
     /// Process an [Approve](enum.TokenInstruction.html) instruction.
+    ///
+    /// `amount` may exceed the current balance of the source account. The
+    /// delegate is always bounded by both the allowance and the balance when
+    /// spending, see [Account::spendable_amount](../state/struct.Account.html#method.spendable_amount).
+    ///
+    /// A zero `amount` is accepted and still records the delegate, which can then
+    /// spend nothing. Use `Revoke` to clear a delegate.
+    ///
+    /// The owner cannot approve itself: transfers and burns signed by a delegate
+    /// are capped by the allowance, which would then cap the owner as well.
     pub fn process_approve(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
@@ -212,204 +702,323 @@ impl Processor {
         let delegate_info = next_account_info(account_info_iter)?;
         let owner_info = next_account_info(account_info_iter)?;
 
-        let mut source_account = Account::unpack(&source_account_info.data.borrow())?;
+        let mut source_account = Self::unpack_account(&source_account_info.data.borrow())?;
         if source_account.is_frozen() {
             return Err(TokenError::AccountFrozen.into());
         }
+        if *delegate_info.key == source_account.owner {
+            return Err(TokenError::InvalidDelegate.into());
+        }
 
         if let Some((mint_info, expected_decimals)) = expected_mint_info {
-            if source_account.mint != mint_info.key {
-                return Err(TokenError::MintMismatch.into());
-            }
-
-            let mint = Mint::unpack(&mint_info.data.borrow_mut())?
-            if expected_decimals != *mint_info.key{
-                return Err(TokenError::MintDecimalsMismatch.into());
-            }
+            let mint = Mint::unpack(Self::split_mint(&mint_info.data.borrow())?.0)?;
+            Self::check_expected_decimals(
+                &mint,
+                Some(expected_decimals),
+                mint_info.key,
+                &source_account.mint,
+            )?;
         }
 
-        Self::validate_owner(program_id, &source_account_info, owner_info, account_info_iter.as_slice())?;
+        Self::validate_owner(
+            program_id,
+            &source_account.owner,
+            owner_info,
+            account_info_iter.as_slice(),
+        )?;
 
-        source_account_info.delegate = COption::Some(*delegate_info.key);
+        source_account.delegate = COption::Some(*delegate_info.key);
         source_account.delegated_amount = amount;
 
-        Account::pack(source_account, &mut source_account_info.borrow_mut());
+        Account::pack(
+            source_account,
+            Self::split_account_mut(&mut source_account_info.data.borrow_mut())?.0,
+        )?;
         Ok(())
     }
 
-    /// Processes an [Approve](enum.TokenInstruction.html) instruction.
+    /// Processes an [ApproveChecked](enum.TokenInstruction.html) instruction.
+    pub fn process_approve_checked(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        decimals: u8,
+    ) -> ProgramResult {
+        Self::process_approve(program_id, accounts, amount, Some(decimals))
+    }
+
+    /// Processes a [Revoke](enum.TokenInstruction.html) instruction.
+    ///
+    /// The revoked delegate and its remaining allowance are logged. Revoking an
+    /// account without a delegate succeeds without changing it. Native accounts
+    /// can be delegated and revoked like any other account.
     pub fn process_revoke(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let source_account_info = next_account_info(account_info_iter)?;
-        
-        let mut source_account = Account::unpack(&source_account_info.data.borrow())?;
-        
         let owner_info = next_account_info(account_info_iter)?;
 
+        let mut source_account = Self::unpack_account(&source_account_info.data.borrow())?;
+
         if source_account.is_frozen() {
             return Err(TokenError::AccountFrozen.into());
         }
 
-        Self::validate_owner(program_id, &source_account.owner, owner_info, account_info_iter.as_slice())?;
+        Self::validate_owner(
+            program_id,
+            &source_account.owner,
+            owner_info,
+            account_info_iter.as_slice(),
+        )?;
 
+        let delegate = match source_account.delegate {
+            COption::Some(delegate) => delegate,
+            COption::None => return Ok(()),
+        };
+        msg!(
+            "Revoking delegate {} with {} tokens approved",
+            delegate,
+            source_account.delegated_amount
+        );
         source_account.delegate = COption::None;
         source_account.delegated_amount = 0;
 
-        Account::pack(source_account, &mut source_account_info.data.borrow_mut())?;
-
-        Ok(()) 
-  }
-
-  pub fn process_set_authority(
-      program_id: &Pubkey,
-      accounts: &[AccountInfo],
-      authority_type: AuthorityType,
-      new_authority: COption<Pubkey>,
-  ) -> ProgramResult {
-      let account_info_iter = &mut accounts.iter();
-      let account_info = next_account_info(account_info_iter)?;
-      let authority_info = next_account_info(account_info_iter)?;
-
-      if account_info.data_len() == Account::get_packed_len() {
-        let mut account = Account::unpack(&account_info.data.borrow())?;
-        
-        if account.is_frozen() {
-              return Err(TokenError::AccountFrozen.into());
-          }
-
-        match authority_type {
-            AuthorityType::AccountOwner => {
-                Self::validate_owner(
-                    program_id, &account.owner, authority_info, account_info_iter.as_slice(),
-                )?;
-                if let COption::Some(authority) = new_authority {
-                    account.owner = authority;
-                } else {
-                    return Err(TokenError::InvalidInstruction.into());
-                }
+        Account::pack(
+            source_account,
+            Self::split_account_mut(&mut source_account_info.data.borrow_mut())?.0,
+        )?;
 
-                account.delegate = COption::None;
-                account.delegated_amount = 0;
+        Ok(())
+    }
+
+    /// Processes a [SetAuthority](enum.TokenInstruction.html) instruction.
+    pub fn process_set_authority(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        authority_type: AuthorityType,
+        new_authority: COption<Pubkey>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let account_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+
+        // Mints stay shorter than token accounts, even with their extensions, so
+        // the data can only ever pass one of the two length checks
+        if Self::split_account(&account_info.data.borrow()).is_ok() {
+            let mut account = Self::unpack_account(&account_info.data.borrow())?;
+
+            if account.is_frozen() {
+                return Err(TokenError::AccountFrozen.into());
+            }
+
+            match authority_type {
+                AuthorityType::AccountOwner => {
+                    if get_extension_bytes(
+                        Self::account_extensions(&account_info.data.borrow()),
+                        ExtensionType::ImmutableOwner,
+                    )?
+                    .is_some()
+                    {
+                        return Err(TokenError::ImmutableOwner.into());
+                    }
+                    Self::validate_owner(
+                        program_id,
+                        &account.owner,
+                        authority_info,
+                        account_info_iter.as_slice(),
+                    )?;
+                    if let COption::Some(authority) = new_authority {
+                        account.owner = authority;
+                    } else {
+                        return Err(TokenError::InvalidInstruction.into());
+                    }
+
+                    // A delegation is granted by a particular owner and never carries
+                    // over to the next one
+                    account.delegate = COption::None;
+                    account.delegated_amount = 0;
+
+                    // Closing a native account pays out its wrapped lamports, so a close
+                    // authority left behind by the previous owner could drain the new
+                    // owner's balance. Non-native accounts can only be closed once empty,
+                    // so their close authority is kept
+                    if account.is_native() {
+                        account.close_authority = COption::None;
+                    }
+                }
+                AuthorityType::CloseAccount => {
+                    let authority = account.close_authority.unwrap_or(account.owner);
+                    Self::validate_owner(
+                        program_id,
+                        &authority,
+                        authority_info,
+                        account_info_iter.as_slice(),
+                    )?;
+                    account.close_authority = new_authority;
+                }
 
-                if account.is_native() {
-                    account.close_authority = COption::None;
+                _ => {
+                    return Err(TokenError::AuthorityTypeNotSupported.into());
                 }
             }
-            AuthorityType::CloseAccount => {
-                let authority = account.close_authority.unwrap_or(account.owner);
-                Self::validate_owner(
-                    program_id, 
-                    &authority, authority_info, account_info_iter.as_slice(),
-                )?;
-                account.close_authority = new_authority;
-            }
-
-            _ =>{
-                return  Err(TokenError::AuthorityTypeNotSupported.into());
-            }
-        }
-        Account::pack(account, &mut account_info.data.borrow_mut())?;
-    } else if account_info.data_len() == Mint::get_packed_len() {
-          let mut mint = Mint::unpack(&account_info.data.borrow())?;
-          match authority_type {
-              AuthorityType::MintTokens => {
-                let mint_authority = mint
-                    .mint_authority
-                    .ok_or(Into::<ProgramError>::into(TokenError::FixedSupply))?;
-                Self::validate_owner(program_id, &mint_authority, authority_info, account_info_iter.as_slice(),)?;
-                mint.mint_authority = new_authority;
-              }
-              AuthorityType::FreezeAccount => {
-                  let freeze_authority = mint
-                  .freeze_authority
-                  .ok_or(Into::<ProgramError>::into(TokenError::MintCannotFreeze))?;
-                  Self::validate_owner(program_id, &freeze_authority, authority_info,
-                     account_info_iter.as_slice(),)?;
+            Account::pack(
+                account,
+                Self::split_account_mut(&mut account_info.data.borrow_mut())?.0,
+            )?;
+        } else if Self::split_mint(&account_info.data.borrow()).is_ok() {
+            // Mints themselves are never frozen, and handing over the freeze authority
+            // leaves already frozen accounts to be thawed by the new one. Once an
+            // authority is `None` it can never be set again.
+            let mut mint = Mint::unpack(Self::split_mint(&account_info.data.borrow())?.0)?;
+            match authority_type {
+                AuthorityType::MintTokens => {
+                    let mint_authority = mint
+                        .mint_authority
+                        .ok_or(Into::<ProgramError>::into(TokenError::FixedSupply))?;
+                    Self::validate_owner(
+                        program_id,
+                        &mint_authority,
+                        authority_info,
+                        account_info_iter.as_slice(),
+                    )?;
+                    mint.mint_authority = new_authority;
+                }
+                AuthorityType::FreezeAccount => {
+                    let freeze_authority = mint
+                        .freeze_authority
+                        .ok_or(Into::<ProgramError>::into(TokenError::MintCannotFreeze))?;
+                    Self::validate_owner(
+                        program_id,
+                        &freeze_authority,
+                        authority_info,
+                        account_info_iter.as_slice(),
+                    )?;
                     mint.freeze_authority = new_authority;
-              }
+                }
 
-              _ => {
-                  return Err(TokenError::AuthorityTypeNotSupported.into());
-              }
-          }
-          Mint::pack(mint, &mut account_info.data.borrow_mut())?;
-      } else {
-          return  Err(ProgramError::InvalidArgument);
-      }
+                _ => {
+                    return Err(TokenError::AuthorityTypeNotSupported.into());
+                }
+            }
+            Mint::pack(
+                mint,
+                Self::split_mint_mut(&mut account_info.data.borrow_mut())?.0,
+            )?;
+        } else {
+            return Err(ProgramError::InvalidArgument);
+        }
 
-    Ok(())
-  }
+        Ok(())
+    }
 
     /// Processes a [MintTo](enum.TokenInstruction.html) instruction.
+    ///
+    /// Minting zero tokens still requires the mint authority's signature but
+    /// changes neither the supply nor the destination.
     pub fn process_mint_to(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
         amount: u64,
         expected_decimals: Option<u8>,
-    ) ->ProgramResult {
+    ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let mint_info = next_account_info(account_info_iter)?;
         let dest_account_info = next_account_info(account_info_iter)?;
         let owner_info = next_account_info(account_info_iter)?;
 
-        let mut dest_account = Account::unpack(&dest_account_info.data.borrow())?;
+        Self::check_account_aliasing(mint_info, dest_account_info)?;
+
+        // The destination is checked before the authority, so minting into a
+        // frozen or native account fails the same way whatever signer is passed
+        let mut dest_account = Self::unpack_account(&dest_account_info.data.borrow())?;
         if dest_account.is_frozen() {
             return Err(TokenError::AccountFrozen.into());
         }
         if dest_account.is_native() {
             return Err(TokenError::NativeNotSupported.into());
         }
-        if mint_info.key != &dest_account.mint {
-            return Err(TokenError::MintMismatch.into());
-        }
-
-        let mut mint = Mint::unpack(&mint_info.data.borrow())?;
-        if let Some(expected_decimals) = expected_decimals {
-            if expected_decimals != mint.decimals {
-                return Err(TokenError::MintDecimalsMismatch.into());
-            }
+        let mut mint = Mint::unpack(Self::split_mint(&mint_info.data.borrow())?.0)?;
+        Self::check_expected_decimals(&mint, expected_decimals, mint_info.key, &dest_account.mint)?;
+        #[cfg(feature = "verbose-logs")]
+        if expected_decimals.is_none() {
+            msg!(
+                "Minting {} with unchecked decimals ({})",
+                amount,
+                mint.decimals
+            );
         }
         match mint.mint_authority {
             COption::Some(mint_authority) => Self::validate_owner(
                 program_id,
-                 &mint_authority, 
-                 owner_info, 
-                 account_info_iter.as_slice(),
+                &mint_authority,
+                owner_info,
+                account_info_iter.as_slice(),
             )?,
-            COption::None => return  Err(TokenError::FixedSupply.into()),
+            COption::None => return Err(TokenError::FixedSupply.into()),
+        }
+
+        // A zero amount still has to be signed for, but leaves all state untouched
+        if amount == 0 {
+            return Ok(());
         }
 
         dest_account.amount = dest_account
-        .amount
-        .checked_add(amount)
-        .ok_or(TokenError::Overflow)?;
+            .amount
+            .checked_add(amount)
+            .ok_or(TokenError::Overflow)?;
 
         mint.supply = mint
-        .supply
-        .checked_add(amount)
-        .ok_or(TokenError::Overflow)?;
+            .supply
+            .checked_add(amount)
+            .ok_or(TokenError::Overflow)?;
+
+        Account::pack(
+            dest_account,
+            Self::split_account_mut(&mut dest_account_info.data.borrow_mut())?.0,
+        )?;
+        Mint::pack(
+            mint,
+            Self::split_mint_mut(&mut mint_info.data.borrow_mut())?.0,
+        )?;
 
-        Account::pack(dest_account, &mut dest_account_info.data.borrow_mut())?;
-        Mint::pack(mint, &mut mint_info.data.borrow_mut())?;
+        #[cfg(feature = "verbose-logs")]
+        msg!(
+            "Minted {} to {}",
+            amount,
+            Self::short_key(dest_account_info.key)
+        );
 
         Ok(())
     }
 
     /// Processes a [Burn](enum.TokenInstruction.html) instruction.
+    ///
+    /// Burning zero tokens is a no-op that is still authorized by the owner or
+    /// delegate. As with [process_transfer](#method.process_transfer), the
+    /// authority can't be the source account itself.
     pub fn process_burn(
         program_id: &Pubkey,
-        accounts:&[AccountInfo],
+        accounts: &[AccountInfo],
         amount: u64,
         expected_decimals: Option<u8>,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
 
         let source_account_info = next_account_info(account_info_iter)?;
-        let mint_info  =next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
         let authority_info = next_account_info(account_info_iter)?;
-        
-        let mut source_account = Account::unpack(&source_account_info.data.borrow())?;
-        let mut mint = Mint::unpack(&mint_info.data.borrow())?;
+
+        Self::check_account_aliasing(source_account_info, mint_info)?;
+        Self::check_account_aliasing(source_account_info, authority_info)?;
+
+        let mut source_account = Self::unpack_account(&source_account_info.data.borrow())?;
+        let (mut mint, burned_total) = {
+            let mint_data = mint_info.data.borrow();
+            let (mint_base, mint_extensions) = Self::split_mint(&mint_data)?;
+            (
+                Mint::unpack(mint_base)?,
+                get_u64_extension(mint_extensions, ExtensionType::BurnedTotal)?,
+            )
+        };
 
         if source_account.is_frozen() {
             return Err(TokenError::AccountFrozen.into());
@@ -420,30 +1029,27 @@ impl Processor {
         if source_account.amount < amount {
             return Err(TokenError::InsufficientFunds.into());
         }
-        if mint_info.key != &source_account.mint {
-            return  Err(TokenError::MintMismatch.into());
-        }
-
-        if let Some(expected_decimals) = expected_decimals {
-            if  expected_decimals != mint.decimals {
-                return Err(TokenError::MintDecimalsMismatch.into());
-            }
-        }
+        Self::check_expected_decimals(
+            &mint,
+            expected_decimals,
+            mint_info.key,
+            &source_account.mint,
+        )?;
 
         match source_account.delegate {
             COption::Some(ref delegate) if authority_info.key == delegate => {
                 Self::validate_owner(
                     program_id,
-                     delegate,
-                      authority_info,
-                       account_info_iter.as_slice(),
-                    )?;
-            
-                if source_account.delegated_amount < amount  {
-                    return Err(TokenError::InsufficientFunds.into());
-                }
-          
-                source_account.delegated_amount = source_account
+                    delegate,
+                    authority_info,
+                    account_info_iter.as_slice(),
+                )?;
+
+                if source_account.delegated_amount < amount {
+                    return Err(TokenError::InsufficientFunds.into());
+                }
+
+                source_account.delegated_amount = source_account
                     .delegated_amount
                     .checked_sub(amount)
                     .ok_or(TokenError::Overflow)?;
@@ -451,7 +1057,7 @@ impl Processor {
                 if source_account.delegated_amount == 0 {
                     source_account.delegate = COption::None;
                 }
-            } 
+            }
             _ => Self::validate_owner(
                 program_id,
                 &source_account.owner,
@@ -460,59 +1066,548 @@ impl Processor {
             )?,
         }
 
+        // As with minting, burning zero only validates the authority
+        if amount == 0 {
+            return Ok(());
+        }
+
         source_account.amount = source_account
-        .amount
-        .checked_sub(amount)
-        .ok_or(TokenError::Overflow)?;
-        
+            .amount
+            .checked_sub(amount)
+            .ok_or(TokenError::Overflow)?;
+
         mint.supply = mint
-         .supply
-         .checked_sub(amount)
-         .ok_or(TokenError::Overflow)?;
+            .supply
+            .checked_sub(amount)
+            .ok_or(TokenError::Overflow)?;
+        let burned_total = burned_total
+            .map(|total| total.checked_add(amount).ok_or(TokenError::Overflow))
+            .transpose()?;
+
+        Account::pack(
+            source_account,
+            Self::split_account_mut(&mut source_account_info.data.borrow_mut())?.0,
+        )?;
+        let mut mint_data = mint_info.data.borrow_mut();
+        let (mint_base, mint_extensions) = Self::split_mint_mut(&mut mint_data)?;
+        Mint::pack(mint, mint_base)?;
+        if let Some(burned_total) = burned_total {
+            set_u64_extension(mint_extensions, ExtensionType::BurnedTotal, burned_total)?;
+        }
+
+        #[cfg(feature = "verbose-logs")]
+        msg!(
+            "Burned {} from {}",
+            amount,
+            Self::short_key(source_account_info.key)
+        );
 
-        Account::pack(source_account, &mut source_account_info.data.borrow_mut())?;
-        Mint::pack(mint, &mut mint_info.data.borrow_mut())?;
         Ok(())
     }
 
-    pub fn process_close_account(
-        program_id: &Pubkey,
-        accounts: &[AccountInfo]
-    ) -> ProgramResult {
+    /// Processes a [CloseAccount](enum.TokenInstruction.html) instruction.
+    ///
+    /// Once a close authority is set, only it can close the account; otherwise
+    /// the owner does. Either may be a multisig.
+    pub fn process_close_account(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let source_account_info = next_account_info(account_info_iter)?;
         let dest_account_info = next_account_info(account_info_iter)?;
         let authority_info = next_account_info(account_info_iter)?;
 
-        let mut source_account = Account::unpack(&source_account_info.data.borrow())?;
+        Self::check_account_aliasing(source_account_info, dest_account_info)?;
+
+        // Lamports sent to a sysvar or to the program itself can never be recovered
+        if sysvar::is_sysvar_id(dest_account_info.key) || dest_account_info.key == program_id {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let source_account = Self::unpack_account(&source_account_info.data.borrow())?;
         if !source_account.is_native() && source_account.amount != 0 {
             return Err(TokenError::NonNativeHasBalance.into());
         }
 
         let authority = source_account
-          .close_authority
-          .unwrap_or(source_account.owner);
-        
+            .close_authority
+            .unwrap_or(source_account.owner);
+
         Self::validate_owner(
             program_id,
-            &authority, 
-            authority_info, 
-        account_info_iter.as_slice(),
+            &authority,
+            authority_info,
+            account_info_iter.as_slice(),
         )?;
         let dest_starting_lamports = dest_account_info.lamports();
+        let reclaimed_lamports = source_account_info.lamports();
         **dest_account_info.lamports.borrow_mut() = dest_starting_lamports
-            .checked_add(source_account_info.lamports())
+            .checked_add(reclaimed_lamports)
             .ok_or(TokenError::Overflow)?;
-            
+
         **source_account_info.lamports.borrow_mut() = 0;
-        source_account.amount = 0;
 
-        Account::pack(source_account, &mut source_account_info.data.borrow_mut())?;
+        #[cfg(feature = "verbose-logs")]
+        msg!(
+            "Reclaimed {} lamports from {} to {}",
+            reclaimed_lamports,
+            source_account_info.key,
+            dest_account_info.key
+        );
+
+        // Wipe the state so the account reads as uninitialized should it be
+        // funded again within the same transaction
+        source_account_info.data.borrow_mut().fill(0);
+        Ok(())
+    }
+
+    /// Processes an [InitializeBurnedTotal](enum.TokenInstruction.html) instruction.
+    pub fn process_initialize_burned_total(accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let mint_info = next_account_info(account_info_iter)?;
+
+        let mut mint_data = mint_info.data.borrow_mut();
+        let (mint_base, mint_extensions) = Self::split_mint_mut(&mut mint_data)?;
+        if Mint::unpack_unchecked(mint_base)?.is_initialized {
+            return Err(TokenError::AlreadyInUse.into());
+        }
+        // the total starts out zeroed
+        init_extension(mint_extensions, ExtensionType::BurnedTotal, 8)?;
+        Ok(())
+    }
+
+    /// Processes an [InitializeCreatedSlot](enum.TokenInstruction.html) instruction.
+    pub fn process_initialize_created_slot(accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let mint_info = next_account_info(account_info_iter)?;
+
+        let mut mint_data = mint_info.data.borrow_mut();
+        let (mint_base, mint_extensions) = Self::split_mint_mut(&mut mint_data)?;
+        if Mint::unpack_unchecked(mint_base)?.is_initialized {
+            return Err(TokenError::AlreadyInUse.into());
+        }
+        // the slot is written when the mint is initialized
+        init_extension(mint_extensions, ExtensionType::CreatedSlot, 8)?;
+        Ok(())
+    }
+
+    /// Processes a [GetMintInfo](enum.TokenInstruction.html) instruction.
+    pub fn process_get_mint_info(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let mint_info = next_account_info(account_info_iter)?;
+        if mint_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mint_data = mint_info.data.borrow();
+        let (mint_base, mint_extensions) = Self::split_mint(&mint_data)?;
+        let mint = Mint::unpack(mint_base)
+            .map_err(|_| Into::<ProgramError>::into(TokenError::InvalidMint))?;
+        let burned_total = get_u64_extension(mint_extensions, ExtensionType::BurnedTotal)?;
+        let created_slot = get_u64_extension(mint_extensions, ExtensionType::CreatedSlot)?;
+        let mut info = [0; 25];
+        info[..8].copy_from_slice(&mint.supply.to_le_bytes());
+        info[8] = mint.decimals;
+        info[9..17].copy_from_slice(&burned_total.unwrap_or(0).to_le_bytes());
+        info[17..].copy_from_slice(&created_slot.unwrap_or(0).to_le_bytes());
+        set_return_data(&info);
+        Ok(())
+    }
+
+    /// Processes an [InitializePausableConfig](enum.TokenInstruction.html) instruction.
+    pub fn process_initialize_pausable_config(
+        accounts: &[AccountInfo],
+        pause_authority: Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let mint_info = next_account_info(account_info_iter)?;
+
+        let mut mint_data = mint_info.data.borrow_mut();
+        let (mint_base, mint_extensions) = Self::split_mint_mut(&mut mint_data)?;
+        if Mint::unpack_unchecked(mint_base)?.is_initialized {
+            return Err(TokenError::AlreadyInUse.into());
+        }
+        init_extension(
+            mint_extensions,
+            ExtensionType::Pausable,
+            PausableConfig::LEN,
+        )?;
+        set_extension(
+            mint_extensions,
+            ExtensionType::Pausable,
+            &PausableConfig {
+                pause_authority,
+                paused: false,
+            },
+        )
+    }
+
+    /// Processes an [InitializeTransferFeeConfig](enum.TokenInstruction.html) instruction.
+    pub fn process_initialize_transfer_fee_config(
+        accounts: &[AccountInfo],
+        transfer_fee: TransferFee,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let mint_info = next_account_info(account_info_iter)?;
+
+        if transfer_fee.transfer_fee_basis_points > ONE_IN_BASIS_POINTS {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let mut mint_data = mint_info.data.borrow_mut();
+        let (mint_base, mint_extensions) = Self::split_mint_mut(&mut mint_data)?;
+        if Mint::unpack_unchecked(mint_base)?.is_initialized {
+            return Err(TokenError::AlreadyInUse.into());
+        }
+        add_extension(
+            mint_extensions,
+            ExtensionType::TransferFeeConfig,
+            &transfer_fee,
+        )
+    }
+
+    /// Processes an [InitializeInterestBearingConfig](enum.TokenInstruction.html) instruction.
+    pub fn process_initialize_interest_bearing_config(
+        accounts: &[AccountInfo],
+        rate: i16,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let mint_info = next_account_info(account_info_iter)?;
+
+        let mut mint_data = mint_info.data.borrow_mut();
+        let (mint_base, mint_extensions) = Self::split_mint_mut(&mut mint_data)?;
+        if Mint::unpack_unchecked(mint_base)?.is_initialized {
+            return Err(TokenError::AlreadyInUse.into());
+        }
+        add_extension(
+            mint_extensions,
+            ExtensionType::InterestBearingConfig,
+            &InterestBearingConfig {
+                initialization_timestamp: Clock::get()?.unix_timestamp,
+                rate,
+            },
+        )
+    }
+
+    fn _process_toggle_freeze_account(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        freeze: bool,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let source_account_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+
+        let mut source_account = Self::unpack_account(&source_account_info.data.borrow())?;
+        if freeze == source_account.is_frozen() {
+            return Err(TokenError::InvalidState.into());
+        }
+        if source_account.is_native() {
+            return Err(TokenError::NativeNotSupported.into());
+        }
+        if mint_info.key != &source_account.mint {
+            return Err(TokenError::MintMismatch.into());
+        }
+
+        let mint = Mint::unpack(Self::split_mint(&mint_info.data.borrow())?.0)?;
+        match mint.freeze_authority {
+            COption::Some(freeze_authority) => Self::validate_owner(
+                program_id,
+                &freeze_authority,
+                authority_info,
+                account_info_iter.as_slice(),
+            ),
+            COption::None => Err(TokenError::MintCannotFreeze.into()),
+        }?;
+
+        source_account.state = if freeze {
+            AccountState::Frozen
+        } else {
+            AccountState::Initialized
+        };
+
+        Account::pack(
+            source_account,
+            Self::split_account_mut(&mut source_account_info.data.borrow_mut())?.0,
+        )?;
+        Ok(())
+    }
+
+    /// Processes a [FreezeAccount](enum.TokenInstruction.html) instruction.
+    pub fn process_freeze_account(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        Self::_process_toggle_freeze_account(program_id, accounts, true)
+    }
+
+    /// Processes a [ThawAccount](enum.TokenInstruction.html) instruction.
+    pub fn process_thaw_account(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        Self::_process_toggle_freeze_account(program_id, accounts, false)
+    }
+
+    /// Processes a [SyncNative](enum.TokenInstruction.html) instruction.
+    pub fn process_sync_native(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let native_account_info = next_account_info(account_info_iter)?;
+        if native_account_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut native_account = Self::unpack_account(&native_account_info.data.borrow())?;
+        if let Some(rent_exempt_reserve) = native_account.rent_exempt_reserve() {
+            // lamports can only be added to the account from outside the program
+            Self::check_native_backing(&native_account, native_account_info.lamports())?;
+            native_account.amount = native_account_info.lamports() - rent_exempt_reserve;
+        } else {
+            return Err(TokenError::NonNativeNotSupported.into());
+        }
+
+        Account::pack(
+            native_account,
+            Self::split_account_mut(&mut native_account_info.data.borrow_mut())?.0,
+        )?;
+        Ok(())
+    }
+
+    /// Processes a [GetAccountDataSize](enum.TokenInstruction.html) instruction.
+    pub fn process_get_account_data_size(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let mint_info = next_account_info(account_info_iter)?;
+        if mint_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mint_data = mint_info.data.borrow();
+        let (mint_base, mint_extensions) = Self::split_mint(&mint_data)?;
+        Mint::unpack(mint_base).map_err(|_| Into::<ProgramError>::into(TokenError::InvalidMint))?;
+        let required_extensions =
+            ExtensionType::get_required_account_extensions(&get_extension_types(mint_extensions)?);
+        // Mirrors the size checks of account initialization
+        let account_len = if required_extensions.is_empty() {
+            Account::get_packed_len()
+        } else {
+            ExtensionType::get_account_len(Account::LEN + 1, &required_extensions)
+        };
+        set_return_data(&(account_len as u64).to_le_bytes());
+        Ok(())
+    }
+
+    /// Processes an [AmountToUiAmount](enum.TokenInstruction.html) instruction.
+    pub fn process_amount_to_ui_amount(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let mint_info = next_account_info(account_info_iter)?;
+        if mint_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mint_data = mint_info.data.borrow();
+        let (mint_base, mint_extensions) = Self::split_mint(&mint_data)?;
+        let mint = Mint::unpack(mint_base)
+            .map_err(|_| Into::<ProgramError>::into(TokenError::InvalidMint))?;
+        let interest_bearing = get_extension::<InterestBearingConfig>(
+            mint_extensions,
+            ExtensionType::InterestBearingConfig,
+        )?;
+        let amount = match interest_bearing {
+            Some(interest_bearing) => interest_bearing
+                .amount_with_interest(amount, Clock::get()?.unix_timestamp)
+                .ok_or(TokenError::Overflow)?,
+            None => amount,
+        };
+        let ui_amount = crate::amount_to_ui_amount_string_trimmed(amount, mint.decimals);
+        set_return_data(ui_amount.as_bytes());
+        Ok(())
+    }
+
+    /// Processes a [UiAmountToAmount](enum.TokenInstruction.html) instruction.
+    pub fn process_ui_amount_to_amount(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ui_amount: &str,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let mint_info = next_account_info(account_info_iter)?;
+        if mint_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mint_data = mint_info.data.borrow();
+        let (mint_base, mint_extensions) = Self::split_mint(&mint_data)?;
+        let mint = Mint::unpack(mint_base)
+            .map_err(|_| Into::<ProgramError>::into(TokenError::InvalidMint))?;
+        let amount = crate::try_ui_amount_into_amount(ui_amount, mint.decimals)?;
+        let interest_bearing = get_extension::<InterestBearingConfig>(
+            mint_extensions,
+            ExtensionType::InterestBearingConfig,
+        )?;
+        let amount = match interest_bearing {
+            Some(interest_bearing) => interest_bearing
+                .amount_without_interest(amount, Clock::get()?.unix_timestamp)
+                .ok_or(TokenError::Overflow)?,
+            None => amount,
+        };
+        set_return_data(&amount.to_le_bytes());
+        Ok(())
+    }
+
+    /// Processes a [SetPause](enum.TokenInstruction.html) instruction.
+    pub fn process_set_pause(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        paused: bool,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let mint_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+
+        let mut mint_data = mint_info.data.borrow_mut();
+        let (mint_base, mint_extensions) = Self::split_mint_mut(&mut mint_data)?;
+        Mint::unpack(mint_base)?;
+        let mut pausable =
+            get_extension::<PausableConfig>(mint_extensions, ExtensionType::Pausable)?
+                .ok_or(TokenError::InvalidState)?;
+
+        Self::validate_owner(
+            program_id,
+            &pausable.pause_authority,
+            authority_info,
+            account_info_iter.as_slice(),
+        )?;
+
+        pausable.paused = paused;
+        set_extension(mint_extensions, ExtensionType::Pausable, &pausable)
+    }
+
+    /// Processes a [WithdrawExcessLamports](enum.TokenInstruction.html) instruction.
+    pub fn process_withdraw_excess_lamports(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let source_account_info = next_account_info(account_info_iter)?;
+        let dest_account_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+
+        let source_account = Self::unpack_account(&source_account_info.data.borrow())?;
+        if source_account.is_native() {
+            return Err(TokenError::NativeNotSupported.into());
+        }
+
+        Self::validate_owner(
+            program_id,
+            &source_account.owner,
+            authority_info,
+            account_info_iter.as_slice(),
+        )?;
+
+        let rent = Rent::get()?;
+        let excess_lamports = source_account_info
+            .lamports()
+            .checked_sub(rent.minimum_balance(source_account_info.data_len()))
+            .ok_or(TokenError::NotRentExempt)?;
+
+        **source_account_info.lamports.borrow_mut() -= excess_lamports;
+        let dest_starting_lamports = dest_account_info.lamports();
+        **dest_account_info.lamports.borrow_mut() = dest_starting_lamports
+            .checked_add(excess_lamports)
+            .ok_or(TokenError::Overflow)?;
+
+        Ok(())
+    }
+
+    /// Processes a [BurnAndClose](enum.TokenInstruction.html) instruction.
+    pub fn process_burn_and_close(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let source_account_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let dest_account_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let signers = account_info_iter.as_slice();
+
+        // Refuse up front rather than burning and then failing to close. Native
+        // tokens can't be burned, so native accounts are refused outright.
+        let source_account = Self::unpack_account(&source_account_info.data.borrow())?;
+        if source_account.is_native() {
+            return Err(TokenError::NativeNotSupported.into());
+        }
+        if source_account.amount > amount {
+            return Err(TokenError::NonNativeHasBalance.into());
+        }
+
+        let burn_accounts = [source_account_info, mint_info, authority_info]
+            .iter()
+            .cloned()
+            .chain(signers)
+            .cloned()
+            .collect::<Vec<_>>();
+        Self::process_burn(program_id, &burn_accounts, amount, None)?;
+
+        let close_accounts = [source_account_info, dest_account_info, authority_info]
+            .iter()
+            .cloned()
+            .chain(signers)
+            .cloned()
+            .collect::<Vec<_>>();
+        Self::process_close_account(program_id, &close_accounts)
+    }
+
+    /// Processes a [GetAccountBalance](enum.TokenInstruction.html) instruction.
+    pub fn process_get_account_balance(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let account_info = next_account_info(account_info_iter)?;
+        if account_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let account = Self::unpack_account(&account_info.data.borrow())?;
+        set_return_data(&account.amount.to_le_bytes());
+        Ok(())
+    }
+
+    /// Processes a [GetAccountState](enum.TokenInstruction.html) instruction.
+    pub fn process_get_account_state(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let account_info = next_account_info(account_info_iter)?;
+        if account_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let account = Self::unpack_account(&account_info.data.borrow())?;
+        let mut state = [0; 10];
+        state[0] = account.state as u8;
+        state[1] = account.is_native() as u8;
+        state[2..].copy_from_slice(&account.amount.to_le_bytes());
+        set_return_data(&state);
         Ok(())
     }
 
     /// Processes an [Instruction](enum.Instruction.html).
     pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
+        // Owner checks, including multisig detection in `validate_owner`, compare
+        // against `program_id`, so it has to be the real one
+        crate::check_program_account(program_id)?;
+        if input.is_empty() {
+            msg!("Error: empty instruction data");
+            return Err(TokenError::InvalidInstruction.into());
+        }
+        // Tags this program doesn't know are refused with an error, never a panic
+        if decode_instruction_type(input).is_err() {
+            msg!("Error: unsupported instruction");
+            return Err(TokenError::InvalidInstruction.into());
+        }
         let instruction = TokenInstruction::unpack(input)?;
 
         match instruction {
@@ -544,58 +1639,319 @@ impl Processor {
                 Self::process_initialize_account2(accounts, owner)
             }
 
+            TokenInstruction::InitializeAccount3 { owner } => {
+                msg!("Instruction: InitializeAccount3");
+                Self::process_initialize_account3(accounts, owner)
+            }
+
             TokenInstruction::InitializeMultisig { m } => {
-                msg!("Instruction: InintializeMultisig");
+                msg!("Instruction: InitializeMultisig");
                 Self::process_initialize_multisig(accounts, m)
             }
 
             TokenInstruction::InitializeMultisig2 { m } => {
-                msg!("Instruction: InintializeMultisig");
-                Self::process_initialize_multisig(accounts, m)
+                msg!("Instruction: InitializeMultisig2");
+                Self::process_initialize_multisig2(accounts, m)
             }
             TokenInstruction::Transfer { amount } => {
                 msg!("Instruction: Transfer");
                 Self::process_transfer(program_id, accounts, amount, None)
-            },
+            }
             TokenInstruction::Approve { amount } => {
                 msg!("Instruction: Approve");
-                Self::process_approve(program_id, accounts, amount,None)
-            },
+                Self::process_approve(program_id, accounts, amount, None)
+            }
             TokenInstruction::Revoke => {
                 msg!("Instruction: Revoke");
                 Self::process_revoke(program_id, accounts)
-            },
+            }
             TokenInstruction::SetAuthority {
                 authority_type,
                 new_authority,
             } => {
                 msg!("Instruction: SetAuthority");
                 Self::process_set_authority(program_id, accounts, authority_type, new_authority)
-            },
-            TokenInstruction::MintTo { amount } =>{
-                msg!("Instruction MintTo");
+            }
+            TokenInstruction::MintTo { amount } => {
+                msg!("Instruction: MintTo");
                 Self::process_mint_to(program_id, accounts, amount, None)
-            },
+            }
             TokenInstruction::Burn { amount } => {
                 msg!("Instruction: Burn");
                 Self::process_burn(program_id, accounts, amount, None)
-            },
-            TokenInstruction::CloseAccount => todo!(),
-            // TokenInstruction::Transfer {amount} => {
-            //     msg!("Instruction: Transfer"):
-            //     Self::process_transfer()
-            // }
+            }
+            TokenInstruction::CloseAccount => {
+                msg!("Instruction: CloseAccount");
+                Self::process_close_account(program_id, accounts)
+            }
+            TokenInstruction::FreezeAccount => {
+                msg!("Instruction: FreezeAccount");
+                Self::process_freeze_account(program_id, accounts)
+            }
+            TokenInstruction::ThawAccount => {
+                msg!("Instruction: ThawAccount");
+                Self::process_thaw_account(program_id, accounts)
+            }
+            TokenInstruction::TransferChecked { amount, decimals } => {
+                msg!("Instruction: TransferChecked");
+                Self::process_transfer(program_id, accounts, amount, Some(decimals))
+            }
+            TokenInstruction::ApproveChecked { amount, decimals } => {
+                msg!("Instruction: ApproveChecked");
+                Self::process_approve_checked(program_id, accounts, amount, decimals)
+            }
+            TokenInstruction::MintToChecked { amount, decimals } => {
+                msg!("Instruction: MintToChecked");
+                Self::process_mint_to(program_id, accounts, amount, Some(decimals))
+            }
+            TokenInstruction::BurnChecked { amount, decimals } => {
+                msg!("Instruction: BurnChecked");
+                Self::process_burn(program_id, accounts, amount, Some(decimals))
+            }
+            TokenInstruction::SyncNative => {
+                msg!("Instruction: SyncNative");
+                Self::process_sync_native(program_id, accounts)
+            }
+            TokenInstruction::InitializeImmutableOwner => {
+                msg!("Instruction: InitializeImmutableOwner");
+                Self::process_initialize_immutable_owner(accounts)
+            }
+            TokenInstruction::GetAccountDataSize => {
+                msg!("Instruction: GetAccountDataSize");
+                Self::process_get_account_data_size(program_id, accounts)
+            }
+            TokenInstruction::AmountToUiAmount { amount } => {
+                msg!("Instruction: AmountToUiAmount");
+                Self::process_amount_to_ui_amount(program_id, accounts, amount)
+            }
+            TokenInstruction::UiAmountToAmount { ui_amount } => {
+                msg!("Instruction: UiAmountToAmount");
+                Self::process_ui_amount_to_amount(program_id, accounts, ui_amount)
+            }
+            TokenInstruction::InitializeBurnedTotal => {
+                msg!("Instruction: InitializeBurnedTotal");
+                Self::process_initialize_burned_total(accounts)
+            }
+            TokenInstruction::GetMintInfo => {
+                msg!("Instruction: GetMintInfo");
+                Self::process_get_mint_info(program_id, accounts)
+            }
+            TokenInstruction::SetPause { paused } => {
+                msg!("Instruction: SetPause");
+                Self::process_set_pause(program_id, accounts, paused)
+            }
+            TokenInstruction::InitializePausableConfig { pause_authority } => {
+                msg!("Instruction: InitializePausableConfig");
+                Self::process_initialize_pausable_config(accounts, pause_authority)
+            }
+            TokenInstruction::WithdrawExcessLamports => {
+                msg!("Instruction: WithdrawExcessLamports");
+                Self::process_withdraw_excess_lamports(program_id, accounts)
+            }
+            TokenInstruction::InitializeCreatedSlot => {
+                msg!("Instruction: InitializeCreatedSlot");
+                Self::process_initialize_created_slot(accounts)
+            }
+            TokenInstruction::BurnAndClose { amount } => {
+                msg!("Instruction: BurnAndClose");
+                Self::process_burn_and_close(program_id, accounts, amount)
+            }
+            TokenInstruction::GetAccountBalance => {
+                msg!("Instruction: GetAccountBalance");
+                Self::process_get_account_balance(program_id, accounts)
+            }
+            TokenInstruction::GetAccountState => {
+                msg!("Instruction: GetAccountState");
+                Self::process_get_account_state(program_id, accounts)
+            }
+            TokenInstruction::TransferCheckedWithFee {
+                amount,
+                decimals,
+                fee,
+            } => {
+                msg!("Instruction: TransferCheckedWithFee");
+                Self::process_transfer_checked_with_fee(program_id, accounts, amount, decimals, fee)
+            }
+            TokenInstruction::InitializeTransferFeeConfig {
+                transfer_fee_basis_points,
+                maximum_fee,
+            } => {
+                msg!("Instruction: InitializeTransferFeeConfig");
+                Self::process_initialize_transfer_fee_config(
+                    accounts,
+                    TransferFee {
+                        transfer_fee_basis_points,
+                        maximum_fee,
+                    },
+                )
+            }
+            TokenInstruction::InitializeInterestBearingConfig { rate } => {
+                msg!("Instruction: InitializeInterestBearingConfig");
+                Self::process_initialize_interest_bearing_config(accounts, rate)
+            }
+
+            TokenInstruction::InitializeMintWithSupply {
+                decimals,
+                mint_authority,
+                freeze_authority,
+                initial_supply,
+            } => {
+                msg!("Instruction: InitializeMintWithSupply");
+                Self::process_initialize_mint_with_supply(
+                    accounts,
+                    decimals,
+                    mint_authority,
+                    freeze_authority,
+                    initial_supply,
+                )
+            }
         }
     }
 
+    /// Splits mint data into the base state and its extensions. Data too short for
+    /// a mint, or long enough to be a token account, is rejected as `InvalidMint`
+    /// before any of it is decoded. Mints and token accounts are told apart by
+    /// length alone, so a mint's extensions can take at most
+    /// `Account::LEN - Mint::LEN - 1` bytes.
+    fn split_mint(data: &[u8]) -> Result<(&[u8], &[u8]), ProgramError> {
+        Self::check_mint_data(data)?;
+        split_extensions(data, Mint::LEN)
+    }
+
+    /// Mutable variant of [split_mint](#method.split_mint)
+    fn split_mint_mut(data: &mut [u8]) -> Result<(&mut [u8], &mut [u8]), ProgramError> {
+        Self::check_mint_data(data)?;
+        split_extensions_mut(data, Mint::LEN)
+    }
+
+    /// Checks that `data` can be mint data, as described for
+    /// [split_mint](#method.split_mint)
+    fn check_mint_data(data: &[u8]) -> ProgramResult {
+        if data.len() < Mint::LEN || data.len() >= Account::LEN {
+            return Err(TokenError::InvalidMint.into());
+        }
+        Ok(())
+    }
+
+    /// Checks that `mint_key`, the key of the unpacked `mint`, is the mint of the
+    /// account involved, `account_mint`, and that the caller's `expected`
+    /// decimals, if any, are the mint's.
+    pub fn check_expected_decimals(
+        mint: &Mint,
+        expected: Option<u8>,
+        mint_key: &Pubkey,
+        account_mint: &Pubkey,
+    ) -> ProgramResult {
+        if mint_key != account_mint {
+            return Err(TokenError::MintMismatch.into());
+        }
+        match expected {
+            Some(expected) if expected != mint.decimals => {
+                Err(TokenError::MintDecimalsMismatch.into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Splits token account data into the base state and its extensions. Data is
+    /// only read as a token account if it is exactly `Account::LEN` bytes long,
+    /// or longer with the account version byte following the base state; an
+    /// unknown version is rejected as `InvalidState`. Data of a multisig's length
+    /// is always rejected, so a multisig, whose signer keys can be chosen to look
+    /// like an account, is never read as one.
+    fn split_account(data: &[u8]) -> Result<(&[u8], &[u8]), ProgramError> {
+        Self::check_account_data(data)?;
+        split_extensions(data, Account::LEN)
+    }
+
+    /// Mutable variant of [split_account](#method.split_account)
+    fn split_account_mut(data: &mut [u8]) -> Result<(&mut [u8], &mut [u8]), ProgramError> {
+        Self::check_account_data(data)?;
+        split_extensions_mut(data, Account::LEN)
+    }
+
+    /// Unpacks the initialized token account in `data`, which is checked as
+    /// described for [split_account](#method.split_account)
+    fn unpack_account(data: &[u8]) -> Result<Account, ProgramError> {
+        if data.len() == Multisig::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Account::unpack_versioned(data)
+    }
+
+    /// Checks that `data` can be token account data, as described for
+    /// [split_account](#method.split_account)
+    fn check_account_data(data: &[u8]) -> ProgramResult {
+        if data.len() == Multisig::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Account::check_version(data)
+    }
+
+    /// Returns the extension entries of token account data, which follow the base
+    /// state and its version byte
+    fn account_extensions(data: &[u8]) -> &[u8] {
+        data.get(Account::LEN + 1..).unwrap_or_default()
+    }
+
+    /// Shortens `key` to the first and last four of its base58 characters, the
+    /// way wallets display addresses
+    #[cfg(feature = "verbose-logs")]
+    fn short_key(key: &Pubkey) -> String {
+        let key = key.to_string();
+        format!("{}..{}", &key[..4], &key[key.len() - 4..])
+    }
+
+    /// Reads the rent from the next account, which must be the rent sysvar, if the
+    /// instruction passes it, or from the sysvar cache otherwise
+    fn get_rent(
+        account_info_iter: &mut std::slice::Iter<AccountInfo>,
+        rent_sysvar_account: bool,
+    ) -> Result<Rent, ProgramError> {
+        if rent_sysvar_account {
+            let rent_sysvar_info = next_account_info(account_info_iter)?;
+            if !sysvar::rent::check_id(rent_sysvar_info.key) {
+                return Err(ProgramError::InvalidArgument);
+            }
+            Rent::from_account_info(rent_sysvar_info)
+        } else {
+            Rent::get()
+        }
+    }
+
+    /// Checks that a native account's token amount is backed by the lamports above
+    /// its rent-exempt reserve
+    fn check_native_backing(account: &Account, lamports: u64) -> ProgramResult {
+        if let Some(rent_exempt_reserve) = account.rent_exempt_reserve() {
+            let available = lamports
+                .checked_sub(rent_exempt_reserve)
+                .ok_or(TokenError::InvalidNativeAccount)?;
+            if account.amount > available {
+                return Err(TokenError::InvalidNativeAccount.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Rejects two account roles that refer to the same account with
+    /// `AccountBorrowFailed`. The handlers release each borrow before taking the
+    /// next one, so aliasing wouldn't panic; these combinations are just refused
+    /// before any state is decoded.
+    fn check_account_aliasing(a: &AccountInfo, b: &AccountInfo) -> ProgramResult {
+        if a.key == b.key {
+            return Err(ProgramError::AccountBorrowFailed);
+        }
+        Ok(())
+    }
+
     /// Validates owner(s) are present
     pub fn validate_owner(
         program_id: &Pubkey,
-        expectted_owner: &Pubkey,
+        expected_owner: &Pubkey,
         owner_account_info: &AccountInfo,
         signers: &[AccountInfo],
     ) -> ProgramResult {
-        if expectted_owner != owner_account_info.key {
+        if expected_owner != owner_account_info.key {
             return Err(TokenError::OwnerMismatch.into());
         }
         if program_id == owner_account_info.owner
@@ -604,7 +1960,11 @@ impl Processor {
             let multisig = Multisig::unpack(&owner_account_info.data.borrow())?;
             let mut num_signers = 0;
             let mut matched = [false; MAX_SIGNERS];
-            for signer in signers.iter() {
+            for (index, signer) in signers.iter().enumerate() {
+                // A signer passed more than once only counts the first time
+                if signers[..index].iter().any(|other| other.key == signer.key) {
+                    continue;
+                }
                 for (position, key) in multisig.signers[0..multisig.n as usize].iter().enumerate() {
                     if key == signer.key && !matched[position] {
                         if !signer.is_signer {
@@ -612,6 +1972,8 @@ impl Processor {
                         }
                         matched[position] = true;
                         num_signers += 1;
+                        // and satisfies a single slot, even if its key fills several
+                        break;
                     }
                 }
             }
@@ -629,7 +1991,8 @@ impl Processor {
 
 impl PrintProgramError for TokenError {
     fn print<E>(&self)
-    where E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + FromPrimitive,
+    where
+        E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + FromPrimitive,
     {
         match self {
             TokenError::NotRentExempt => msg!("Error: Lamport balance below rent-exempt threshold"),
@@ -639,10 +2002,16 @@ impl PrintProgramError for TokenError {
             TokenError::OwnerMismatch => msg!("Error: owner does not match"),
             TokenError::FixedSupply => msg!("Errror: the total supply of this tokne is fixed"),
             TokenError::AlreadyInUse => msg!("Error: account or token already in use"),
-            TokenError::InvalidNumberOfProvidedSigners => msg!("Error: Invalid number of provided signers"),
-            TokenError::InvalidNumberOfRequiredSigners => msg!("Error: Invalid number of required signers"),
+            TokenError::InvalidNumberOfProvidedSigners => {
+                msg!("Error: Invalid number of provided signers")
+            }
+            TokenError::InvalidNumberOfRequiredSigners => {
+                msg!("Error: Invalid number of required signers")
+            }
             TokenError::UninitializedState => msg!("Error: State is unintialized"),
-            TokenError::NativeNotSupported => msg!("Error: Instruction does not support native tokens"),
+            TokenError::NativeNotSupported => {
+                msg!("Error: Instruction does not support native tokens")
+            }
             TokenError::NonNativeHasBalance => {
                 msg!("Error: Non-native account can only be closed if its balance if zero")
             }
@@ -654,8 +2023,23 @@ impl PrintProgramError for TokenError {
             }
             TokenError::MintCannotFreeze => msg!("Error: This token mint cannot freeze accounts"),
             TokenError::AccountFrozen => msg!("Error: Account is frozen"),
-            TokenError::MintDecimalsMismatch => msg!("Error: decimals diffrent from the Mint decimals"),
-            TokenError::NonNativeNotSupported => msg!("Error: Instruction dose not support non-native tokens"),
+            TokenError::MintDecimalsMismatch => {
+                msg!("Error: decimals diffrent from the Mint decimals")
+            }
+            TokenError::NonNativeNotSupported => {
+                msg!("Error: Instruction dose not support non-native tokens")
+            }
+            TokenError::InvalidNativeAccount => {
+                msg!("Error: Native account amount and lamports are out of sync")
+            }
+            TokenError::MintPaused => msg!("Error: Transfers of this mint are paused"),
+            TokenError::MintRequiredForTransfer => {
+                msg!("Error: Transfers of this mint must supply the mint")
+            }
+            TokenError::ImmutableOwner => msg!("Error: Account owner cannot be changed"),
+            TokenError::InvalidMintDecimals => msg!("Error: Invalid number of mint decimals"),
+            TokenError::InvalidDelegate => msg!("Error: Invalid delegate"),
+            TokenError::FeeMismatch => msg!("Error: fee does not match the mint's transfer fee"),
         }
     }
 }
@@ -1,14 +1,25 @@
 //! State transition types
 
-use crate::instruction::MAX_SIGNERS;
+use crate::{
+    error::TokenError,
+    extension::{get_extension_types, split_extensions, ExtensionType},
+    instruction::MAX_SIGNERS,
+};
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 use num_enum::TryFromPrimitive;
 use solana_program::{
+    entrypoint::ProgramResult,
     program_error::ProgramError,
     program_option::COption,
     program_pack::{IsInitialized, Pack, Sealed},
     pubkey::Pubkey,
 };
+use std::convert::TryFrom;
+
+/// Largest `decimals` a mint may be initialized with. `10^19` is the largest
+/// power of ten that fits in a `u64`, which leaves such a mint room for only
+/// about 1.8 whole tokens.
+pub const MAX_DECIMALS: u8 = 19;
 
 /// Mint data.
 #[repr(C)]
@@ -27,6 +38,14 @@ pub struct Mint {
     /// Optional authority to freeze token accounts.
     pub freeze_authority: COption<Pubkey>,
 }
+impl Mint {
+    /// Lists the extensions stored after the base mint in `data`, the mint
+    /// account's full data
+    pub fn extensions(&self, data: &[u8]) -> Result<Vec<ExtensionType>, ProgramError> {
+        let (_, extensions) = split_extensions(data, Self::LEN)?;
+        get_extension_types(extensions)
+    }
+}
 impl Sealed for Mint {}
 impl IsInitialized for Mint {
     fn is_initialized(&self) -> bool {
@@ -80,7 +99,29 @@ impl Pack for Mint {
     }
 }
 
+/// Every deployed mint and every client decoding one relies on this size, so a
+/// field added to `Mint` must not change it.
+const _: () = assert!(Mint::LEN == 82);
+
+/// Layout version of the current `Account` state. Account data that is exactly
+/// `Account::LEN` bytes long carries no version byte and is implicitly version 0.
+pub const ACCOUNT_VERSION: u8 = 0;
+
+/// Length of the packed `Account` fields, excluding any trailing checksum
+const ACCOUNT_FIELDS_LEN: usize = 165;
+
+/// Length of the checksum trailing the packed `Account` fields
+#[cfg(feature = "account-checksum")]
+const ACCOUNT_CHECKSUM_LEN: usize = 4;
+#[cfg(not(feature = "account-checksum"))]
+const ACCOUNT_CHECKSUM_LEN: usize = 0;
+
 /// Account data.
+///
+/// With the `account-checksum` feature enabled the packed representation is
+/// followed by a 4-byte little-endian FNV-1a checksum of the packed fields,
+/// growing `Account::LEN` from 165 to 169 bytes. Accounts packed without the
+/// feature cannot be read with it and vice versa.
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Account {
@@ -113,6 +154,43 @@ impl Account {
     pub fn is_native(&self) -> bool {
         self.is_native.is_some()
     }
+    /// Returns the lamports a native account keeps back to stay rent-exempt, or
+    /// `None` if the account isn't native
+    pub fn rent_exempt_reserve(&self) -> Option<u64> {
+        match self.is_native {
+            COption::Some(rent_exempt_reserve) => Some(rent_exempt_reserve),
+            COption::None => None,
+        }
+    }
+    /// Returns the amount `authority` may move out of this account, or `None` if
+    /// `authority` is neither the delegate nor the owner
+    pub fn spendable_amount(&self, authority: &Pubkey) -> Option<u64> {
+        match self.delegate {
+            COption::Some(ref delegate) if authority == delegate => {
+                Some(self.amount.min(self.delegated_amount))
+            }
+            _ if authority == &self.owner => Some(self.amount),
+            _ => None,
+        }
+    }
+    /// Checks that account data, which may be followed by a layout version byte,
+    /// is of a layout version this program reads
+    pub fn check_version(input: &[u8]) -> ProgramResult {
+        let version = match input.len() {
+            len if len == Self::LEN => ACCOUNT_VERSION,
+            len if len > Self::LEN => input[Self::LEN],
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        match version {
+            ACCOUNT_VERSION => Ok(()),
+            _ => Err(TokenError::InvalidState.into()),
+        }
+    }
+    /// Unpacks account data that may be followed by a layout version byte
+    pub fn unpack_versioned(input: &[u8]) -> Result<Self, ProgramError> {
+        Self::check_version(input)?;
+        Self::unpack(&input[..Self::LEN])
+    }
 }
 impl Sealed for Account {}
 impl IsInitialized for Account {
@@ -121,25 +199,39 @@ impl IsInitialized for Account {
     }
 }
 impl Pack for Account {
-    const LEN: usize = 165;
+    const LEN: usize = ACCOUNT_FIELDS_LEN + ACCOUNT_CHECKSUM_LEN;
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        #[cfg(feature = "account-checksum")]
+        verify_account_checksum(src)?;
+        // Decoding through fixed-size array references keeps every field a
+        // whole-array copy, which the compiler lowers to wide loads. This is
+        // the hottest path for indexers; see `benches/unpack.rs`.
         let src = array_ref![src, 0, 165];
         let (mint, owner, amount, delegate, state, is_native, delegated_amount, close_authority) =
             array_refs![src, 32, 32, 8, 36, 1, 12, 8, 36];
+        let delegated_amount = u64::from_le_bytes(*delegated_amount);
+        let delegate = match unpack_coption_key(delegate)? {
+            // An all-zero delegate with nothing delegated cannot have come from an
+            // approval, so it is read back as no delegate at all
+            COption::Some(delegate) if delegate == Pubkey::default() && delegated_amount == 0 => {
+                COption::None
+            }
+            delegate => delegate,
+        };
         Ok(Account {
             mint: Pubkey::new_from_array(*mint),
             owner: Pubkey::new_from_array(*owner),
             amount: u64::from_le_bytes(*amount),
-            delegate: unpack_coption_key(delegate)?,
+            delegate,
             state: AccountState::try_from_primitive(state[0])
                 .or(Err(ProgramError::InvalidAccountData))?,
             is_native: unpack_coption_u64(is_native)?,
-            delegated_amount: u64::from_le_bytes(*delegated_amount),
+            delegated_amount,
             close_authority: unpack_coption_key(close_authority)?,
         })
     }
     fn pack_into_slice(&self, dst: &mut [u8]) {
-        let dst = array_mut_ref![dst, 0, 165];
+        let fields_dst = array_mut_ref![dst, 0, 165];
         let (
             mint_dst,
             owner_dst,
@@ -149,7 +241,7 @@ impl Pack for Account {
             is_native_dst,
             delegated_amount_dst,
             close_authority_dst,
-        ) = mut_array_refs![dst, 32, 32, 8, 36, 1, 12, 8, 36];
+        ) = mut_array_refs![fields_dst, 32, 32, 8, 36, 1, 12, 8, 36];
         let &Account {
             ref mint,
             ref owner,
@@ -168,6 +260,41 @@ impl Pack for Account {
         pack_coption_u64(is_native, is_native_dst);
         *delegated_amount_dst = delegated_amount.to_le_bytes();
         pack_coption_key(close_authority, close_authority_dst);
+        #[cfg(feature = "account-checksum")]
+        {
+            let checksum = account_checksum(dst);
+            let checksum_dst = array_mut_ref![dst, ACCOUNT_FIELDS_LEN, ACCOUNT_CHECKSUM_LEN];
+            *checksum_dst = checksum.to_le_bytes();
+        }
+    }
+}
+
+/// The account fields keep the 165-byte SPL layout that deployed token accounts
+/// and clients depend on; only the opt-in checksum may follow them.
+const _: () = assert!(Account::LEN == 165 + ACCOUNT_CHECKSUM_LEN);
+
+/// FNV-1a checksum over the packed `Account` fields
+#[cfg(feature = "account-checksum")]
+fn account_checksum(src: &[u8]) -> u32 {
+    src[..ACCOUNT_FIELDS_LEN]
+        .iter()
+        .fold(0x811c_9dc5, |hash: u32, byte| {
+            (hash ^ *byte as u32).wrapping_mul(0x0100_0193)
+        })
+}
+
+/// Checks the trailing checksum of a packed `Account`. Freshly allocated, all-zero
+/// data has no checksum yet and is accepted as is.
+#[cfg(feature = "account-checksum")]
+fn verify_account_checksum(src: &[u8]) -> Result<(), ProgramError> {
+    let src = array_ref![src, 0, Account::LEN];
+    let (fields, checksum) = array_refs![src, ACCOUNT_FIELDS_LEN, ACCOUNT_CHECKSUM_LEN];
+    if src.iter().all(|byte| *byte == 0)
+        || u32::from_le_bytes(*checksum) == account_checksum(fields)
+    {
+        Ok(())
+    } else {
+        Err(TokenError::InvalidState.into())
     }
 }
 
@@ -185,6 +312,10 @@ pub enum AccountState {
     Frozen,
 }
 
+// Written out rather than derived: `TryFromPrimitive` would read a `#[default]`
+// variant as the fallback for unknown bytes, decoding corrupt states as
+// `Uninitialized` instead of rejecting them.
+#[allow(clippy::derivable_impls)]
 impl Default for AccountState {
     fn default() -> Self {
         AccountState::Uninitialized
@@ -227,7 +358,7 @@ impl Pack for Multisig {
             signers: [Pubkey::new_from_array([0u8; 32]); MAX_SIGNERS],
         };
         for (src, dst) in signers_flat.chunks(32).zip(result.signers.iter_mut()) {
-            *dst = Pubkey::new(src);
+            *dst = Pubkey::try_from(src).map_err(|_| ProgramError::InvalidAccountData)?;
         }
         Ok(result)
     }
@@ -245,6 +376,10 @@ impl Pack for Multisig {
     }
 }
 
+/// Deployed multisig accounts were allocated at this size, with room for
+/// `MAX_SIGNERS` signers, so the layout is frozen.
+const _: () = assert!(Multisig::LEN == 355 && Multisig::LEN == 3 + 32 * MAX_SIGNERS);
+
 // Helpers
 fn pack_coption_key(src: &COption<Pubkey>, dst: &mut [u8; 36]) {
     let (tag, body) = mut_array_refs![dst, 4, 32];
@@ -0,0 +1,261 @@
+//! Helpers shared by the token program integration tests
+
+#![allow(dead_code)]
+
+use solana_program::{
+    clock::Clock,
+    entrypoint::{ProgramResult, SUCCESS},
+    instruction::Instruction,
+    program_option::COption,
+    program_pack::{IsInitialized, Pack},
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey::Pubkey,
+    rent::Rent,
+};
+use solana_sdk::account::{
+    create_account_for_test, create_is_signer_account_infos, Account as SolanaAccount,
+};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+};
+use token::{
+    processor::Processor,
+    state::{Account, AccountState, Mint},
+};
+
+/// Runs `instruction` through the program entry point against `accounts`, which
+/// are matched up with the instruction's account metas in order
+pub fn do_process_instruction(
+    instruction: Instruction,
+    accounts: Vec<&mut SolanaAccount>,
+) -> ProgramResult {
+    let mut meta = instruction
+        .accounts
+        .iter()
+        .zip(accounts)
+        .map(|(account_meta, account)| (&account_meta.pubkey, account_meta.is_signer, account))
+        .collect::<Vec<_>>();
+    let account_infos = create_is_signer_account_infos(&mut meta);
+    Processor::process(&instruction.program_id, &account_infos, &instruction.data)
+}
+
+thread_local! {
+    static LOGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    static SLOT: Cell<u64> = const { Cell::new(0) };
+    static UNIX_TIMESTAMP: Cell<i64> = const { Cell::new(0) };
+    static RETURN_DATA: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+    // The fields of the rent, as `Rent` itself is only `Copy` in some versions
+    static RENT: Cell<(u64, f64, u8)> = Cell::new(rent_fields(&Rent::default()));
+}
+
+struct ProgramStubs;
+impl SyscallStubs for ProgramStubs {
+    fn sol_log(&self, message: &str) {
+        LOGS.with(|logs| logs.borrow_mut().push(message.to_string()));
+    }
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            slot: SLOT.with(Cell::get),
+            unix_timestamp: UNIX_TIMESTAMP.with(Cell::get),
+            ..Clock::default()
+        };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        SUCCESS
+    }
+    fn sol_set_return_data(&self, data: &[u8]) {
+        RETURN_DATA.with(|return_data| *return_data.borrow_mut() = data.to_vec());
+    }
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        Some((
+            token::id(),
+            RETURN_DATA.with(|return_data| return_data.borrow().clone()),
+        ))
+    }
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let (lamports_per_byte_year, exemption_threshold, burn_percent) = RENT.with(Cell::get);
+        let rent = Rent {
+            lamports_per_byte_year,
+            exemption_threshold,
+            burn_percent,
+        };
+        unsafe { *(var_addr as *mut Rent) = rent };
+        SUCCESS
+    }
+}
+
+/// Lets `Rent::get` return the rent set with [set_rent](fn.set_rent.html) and
+/// `Clock::get` the slot and timestamp set with [set_slot](fn.set_slot.html) and
+/// [set_unix_timestamp](fn.set_unix_timestamp.html) outside of the runtime, and
+/// records the program logs and the return data of the calling thread
+pub fn set_program_stubs() {
+    set_syscall_stubs(Box::new(ProgramStubs));
+}
+
+/// Sets the slot `Clock::get` reports on the calling thread
+pub fn set_slot(slot: u64) {
+    SLOT.with(|current| current.set(slot));
+}
+
+/// Sets the Unix timestamp `Clock::get` reports on the calling thread
+pub fn set_unix_timestamp(unix_timestamp: i64) {
+    UNIX_TIMESTAMP.with(|current| current.set(unix_timestamp));
+}
+
+/// Sets the rent `Rent::get` reports on the calling thread, the default rent
+/// unless changed
+pub fn set_rent(rent: &Rent) {
+    RENT.with(|current| current.set(rent_fields(rent)));
+}
+
+fn rent_fields(rent: &Rent) -> (u64, f64, u8) {
+    (
+        rent.lamports_per_byte_year,
+        rent.exemption_threshold,
+        rent.burn_percent,
+    )
+}
+
+/// Returns and clears the program logs recorded on the calling thread
+pub fn take_logs() -> Vec<String> {
+    LOGS.with(|logs| logs.borrow_mut().drain(..).collect())
+}
+
+/// Returns and clears the return data set on the calling thread
+pub fn take_return_data() -> Vec<u8> {
+    RETURN_DATA.with(|return_data| return_data.borrow_mut().split_off(0))
+}
+
+/// Creates a rent sysvar account holding the default rent
+pub fn rent_sysvar() -> SolanaAccount {
+    rent_sysvar_with(&Rent::default())
+}
+
+/// Creates a rent sysvar account holding `rent`
+pub fn rent_sysvar_with(rent: &Rent) -> SolanaAccount {
+    create_account_for_test(rent)
+}
+
+/// Creates a zeroed, rent-exempt account of `len` bytes owned by the token program
+pub fn uninitialized_account(len: usize) -> SolanaAccount {
+    SolanaAccount::new(Rent::default().minimum_balance(len), len, &token::id())
+}
+
+/// Creates a rent-exempt account owned by the token program holding `state`
+pub fn packed_account<T: Pack>(state: &T) -> SolanaAccount {
+    let mut data = vec![0; T::LEN];
+    state.pack_into_slice(&mut data);
+    SolanaAccount {
+        lamports: Rent::default().minimum_balance(T::LEN),
+        data,
+        owner: token::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Creates an initialized token account
+pub fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> SolanaAccount {
+    packed_account(&Account {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: AccountState::Initialized,
+        ..Account::default()
+    })
+}
+
+/// Creates an initialized mint
+pub fn mint_account(mint_authority: Option<&Pubkey>, supply: u64, decimals: u8) -> SolanaAccount {
+    packed_account(&Mint {
+        mint_authority: mint_authority.cloned().into(),
+        supply,
+        decimals,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    })
+}
+
+/// Creates an initialized native token account holding `amount` wrapped lamports
+pub fn native_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> SolanaAccount {
+    let rent_exempt_reserve = Rent::default().minimum_balance(Account::LEN);
+    let mut account = packed_account(&Account {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: AccountState::Initialized,
+        is_native: COption::Some(rent_exempt_reserve),
+        ..Account::default()
+    });
+    account.lamports = rent_exempt_reserve + amount;
+    account
+}
+
+/// In-memory accounts, keyed by address, that instructions are processed against
+///
+/// Each instruction is handed the accounts named by its metas, with addresses
+/// never added reading as empty system accounts. An address named twice is the
+/// same account both times. As in the runtime, the accounts only change when
+/// the instruction succeeds.
+#[derive(Default)]
+pub struct AccountHarness {
+    accounts: HashMap<Pubkey, SolanaAccount>,
+}
+
+impl AccountHarness {
+    /// Adds or replaces the account at `key`
+    pub fn insert(&mut self, key: Pubkey, account: SolanaAccount) {
+        self.accounts.insert(key, account);
+    }
+
+    /// Returns the account at `key`
+    pub fn get(&self, key: &Pubkey) -> &SolanaAccount {
+        &self.accounts[key]
+    }
+
+    /// Unpacks the state held by the account at `key`
+    pub fn unpack<T: Pack + IsInitialized>(&self, key: &Pubkey) -> T {
+        T::unpack(&self.get(key).data).unwrap()
+    }
+
+    /// Runs `instruction` through the program entry point, keeping the account
+    /// changes only if it succeeds
+    pub fn process(&mut self, instruction: &Instruction) -> ProgramResult {
+        let mut keys = Vec::<(Pubkey, bool)>::new();
+        for meta in &instruction.accounts {
+            match keys.iter_mut().find(|(key, _)| *key == meta.pubkey) {
+                Some((_, is_signer)) => *is_signer |= meta.is_signer,
+                None => keys.push((meta.pubkey, meta.is_signer)),
+            }
+        }
+        let mut accounts = keys
+            .iter()
+            .map(|(key, _)| self.accounts.get(key).cloned().unwrap_or_default())
+            .collect::<Vec<_>>();
+
+        let result = {
+            let mut meta = keys
+                .iter()
+                .zip(accounts.iter_mut())
+                .map(|((key, is_signer), account)| (key, *is_signer, account))
+                .collect::<Vec<_>>();
+            let unique_infos = create_is_signer_account_infos(&mut meta);
+            let account_infos = instruction
+                .accounts
+                .iter()
+                .map(|meta| {
+                    let index = keys.iter().position(|(key, _)| *key == meta.pubkey);
+                    unique_infos[index.unwrap()].clone()
+                })
+                .collect::<Vec<_>>();
+            Processor::process(&instruction.program_id, &account_infos, &instruction.data)
+        };
+
+        if result.is_ok() {
+            for ((key, _), account) in keys.into_iter().zip(accounts) {
+                self.accounts.insert(key, account);
+            }
+        }
+        result
+    }
+}
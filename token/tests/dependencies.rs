@@ -0,0 +1,41 @@
+//! The program builds for BPF, so it may only depend on `solana_program`;
+//! `solana_sdk` is limited to these tests
+
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+use token::processor::Processor;
+
+/// Names of the crates listed in the `[section]` table of the manifest
+fn manifest_dependencies(section: &str) -> Vec<&'static str> {
+    let manifest = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"));
+    let header = format!("[{}]", section);
+    manifest
+        .lines()
+        .skip_while(|line| line.trim() != header)
+        .skip(1)
+        .take_while(|line| !line.trim_start().starts_with('['))
+        .filter_map(|line| line.split('=').next())
+        .map(str::trim)
+        .filter(|name| !name.is_empty() && !name.starts_with('#'))
+        .collect()
+}
+
+#[test]
+fn test_library_does_not_depend_on_solana_sdk() {
+    // solana-program doesn't pull in solana-sdk, so the direct dependencies are
+    // all that needs checking
+    for section in ["dependencies", "build-dependencies"] {
+        let dependencies = manifest_dependencies(section);
+        assert!(
+            !dependencies.contains(&"solana-sdk"),
+            "the library depends on solana-sdk: {:?}",
+            dependencies
+        );
+    }
+    assert!(manifest_dependencies("dependencies").contains(&"solana-program"));
+}
+
+#[test]
+fn test_processor_returns_entrypoint_program_result() {
+    let process: fn(&Pubkey, &[AccountInfo], &[u8]) -> ProgramResult = Processor::process;
+    assert!(process(&token::id(), &[], &[]).is_err());
+}
@@ -0,0 +1,16 @@
+//! Checks that the library defines the program's entrypoint unless it is built
+//! with the `no-entrypoint` feature
+
+#![cfg(not(feature = "no-entrypoint"))]
+
+use solana_program::pubkey::Pubkey;
+
+#[test]
+fn test_library_exports_entrypoint() {
+    // Only links while the library defines the program's entrypoint symbol
+    extern "C" {
+        fn entrypoint(input: *mut u8) -> u64;
+    }
+    std::hint::black_box(entrypoint as unsafe extern "C" fn(*mut u8) -> u64);
+    assert!(token::check_program_account(&Pubkey::new_unique()).is_err());
+}
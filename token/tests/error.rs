@@ -0,0 +1,32 @@
+use num_traits::FromPrimitive;
+use solana_program::{decode_error::DecodeError, program_error::ProgramError};
+use token::error::TokenError;
+
+#[test]
+fn test_error_round_trip() {
+    let errors = (0..).map_while(TokenError::from_u32).collect::<Vec<_>>();
+    assert_eq!(Some(&TokenError::FeeMismatch), errors.last());
+
+    for (code, error) in errors.into_iter().enumerate() {
+        let program_error = ProgramError::from(error.clone());
+        assert_eq!(ProgramError::Custom(code as u32), program_error);
+        match program_error {
+            ProgramError::Custom(code) => {
+                assert_eq!(Some(error), TokenError::from_u32(code));
+            }
+            _ => unreachable!(),
+        }
+    }
+    assert_eq!("TokenError", <TokenError as DecodeError<()>>::type_of());
+}
+
+#[test]
+fn test_error_discriminants_are_stable() {
+    assert_eq!(0, TokenError::NotRentExempt as u32);
+    assert_eq!(6, TokenError::AlreadyInUse as u32);
+    assert_eq!(7, TokenError::InvalidNumberOfProvidedSigners as u32);
+    assert_eq!(8, TokenError::InvalidNumberOfRequiredSigners as u32);
+    assert_eq!(11, TokenError::NonNativeHasBalance as u32);
+    assert_eq!(19, TokenError::NonNativeNotSupported as u32);
+    assert_eq!(21, TokenError::MintPaused as u32);
+}
@@ -0,0 +1,239 @@
+use solana_program::{program_error::ProgramError, program_pack::Pack, pubkey::Pubkey};
+use token::{
+    error::TokenError,
+    extension::{
+        add_extension, add_u64_extension, get_extension, get_extension_types, get_u64_extension,
+        init_extension, set_extension, set_u64_extension, split_extensions_mut, ExtensionType,
+        FeeRounding, InterestBearingConfig, PausableConfig, TransferFee, SECONDS_PER_YEAR,
+    },
+    state::Mint,
+};
+
+#[test]
+fn test_transfer_fee_rounding() {
+    let fee = TransferFee {
+        transfer_fee_basis_points: 25,
+        maximum_fee: u64::MAX,
+    };
+    assert_eq!(FeeRounding::Up, FeeRounding::default());
+
+    // 0.25% of 1_000
+    assert_eq!(Some(3), fee.calculate_fee(1_000, FeeRounding::Up));
+    assert_eq!(Some(2), fee.calculate_fee(1_000, FeeRounding::Down));
+    // exact fees are not rounded
+    assert_eq!(Some(25), fee.calculate_fee(10_000, FeeRounding::Up));
+    assert_eq!(Some(25), fee.calculate_fee(10_000, FeeRounding::Down));
+
+    // tiny amounts only round to a zero fee when rounding down
+    assert_eq!(Some(1), fee.calculate_fee(1, FeeRounding::Up));
+    assert_eq!(Some(0), fee.calculate_fee(1, FeeRounding::Down));
+    assert_eq!(Some(0), fee.calculate_fee(0, FeeRounding::Up));
+}
+
+#[test]
+fn test_transfer_fee_large_amounts() {
+    let fee = TransferFee {
+        transfer_fee_basis_points: 10_000,
+        maximum_fee: u64::MAX,
+    };
+    assert_eq!(Some(u64::MAX), fee.calculate_fee(u64::MAX, FeeRounding::Up));
+
+    let fee = TransferFee {
+        transfer_fee_basis_points: 9_999,
+        maximum_fee: u64::MAX,
+    };
+    // u64::MAX * 9_999 / 10_000 = u64::MAX - 1844674407370955.1615
+    let exact = u64::MAX - 1_844_674_407_370_956;
+    assert_eq!(Some(exact), fee.calculate_fee(u64::MAX, FeeRounding::Down));
+    assert_eq!(
+        Some(exact + 1),
+        fee.calculate_fee(u64::MAX, FeeRounding::Up)
+    );
+}
+
+#[test]
+fn test_transfer_fee_cap() {
+    let fee = TransferFee {
+        transfer_fee_basis_points: 100,
+        maximum_fee: 5_000,
+    };
+    assert_eq!(Some(4_999), fee.calculate_fee(499_900, FeeRounding::Up));
+    assert_eq!(Some(5_000), fee.calculate_fee(500_000, FeeRounding::Up));
+    assert_eq!(Some(5_000), fee.calculate_fee(u64::MAX, FeeRounding::Up));
+
+    let invalid = TransferFee {
+        transfer_fee_basis_points: 10_001,
+        maximum_fee: u64::MAX,
+    };
+    assert_eq!(None, invalid.calculate_fee(1, FeeRounding::Up));
+}
+
+#[test]
+fn test_interest_bearing_amounts() {
+    let year = SECONDS_PER_YEAR;
+    let config = InterestBearingConfig {
+        initialization_timestamp: year,
+        rate: 1_000,
+    };
+    assert_eq!(Some(1_000), config.amount_with_interest(1_000, year));
+    // 10% compounded continuously, e^0.1 = 1.10517...
+    assert_eq!(Some(1_105), config.amount_with_interest(1_000, 2 * year));
+    assert_eq!(Some(1_000), config.amount_without_interest(1_105, 2 * year));
+    assert_eq!(None, config.amount_with_interest(u64::MAX, 2 * year));
+    assert_eq!(None, config.amount_with_interest(1, i64::MIN));
+
+    // a negative rate lets the amount decay, e^-0.1 = 0.90483...
+    let config = InterestBearingConfig {
+        initialization_timestamp: 0,
+        rate: -1_000,
+    };
+    assert_eq!(Some(904), config.amount_with_interest(1_000, year));
+    assert_eq!(Some(1_000), config.amount_without_interest(905, year));
+}
+
+#[test]
+fn test_interest_bearing_precision() {
+    let year = SECONDS_PER_YEAR;
+    let config = InterestBearingConfig {
+        initialization_timestamp: 0,
+        rate: 500,
+    };
+    // the fixed-point factor stays within 1e-16 of e^0.05 = 1.05127109637602403969...
+    let exact = 1_051_271_096_376_024_039u64;
+    let with_interest = config
+        .amount_with_interest(1_000_000_000_000_000_000, year)
+        .unwrap();
+    assert!(with_interest.abs_diff(exact) <= exact / 10_000_000_000_000_000);
+    // and converting back lands on the raw amount again
+    assert_eq!(
+        Some(1_000_000_000_000_000_000),
+        config.amount_without_interest(with_interest, year)
+    );
+}
+
+#[test]
+fn test_interest_bearing_timestamp_extremes() {
+    // the timespan itself doesn't fit into an i64
+    let config = InterestBearingConfig {
+        initialization_timestamp: i64::MIN,
+        rate: 1,
+    };
+    assert_eq!(None, config.amount_with_interest(1, i64::MAX));
+    assert_eq!(None, config.amount_without_interest(1, i64::MAX));
+
+    // the largest exponents either overflow or decay the amount to nothing
+    let growing = InterestBearingConfig {
+        initialization_timestamp: 0,
+        rate: i16::MAX,
+    };
+    let decaying = InterestBearingConfig {
+        initialization_timestamp: 0,
+        rate: i16::MIN,
+    };
+    for unix_timestamp in [i64::MIN, i64::MAX] {
+        let (overflowing, vanishing) = if unix_timestamp > 0 {
+            (growing, decaying)
+        } else {
+            (decaying, growing)
+        };
+        assert_eq!(None, overflowing.amount_with_interest(1, unix_timestamp));
+        assert_eq!(None, overflowing.amount_without_interest(1, unix_timestamp));
+        assert_eq!(
+            Some(0),
+            vanishing.amount_with_interest(u64::MAX, unix_timestamp)
+        );
+        assert_eq!(None, vanishing.amount_without_interest(1, unix_timestamp));
+    }
+}
+
+#[test]
+fn test_mint_extensions() {
+    let mint = Mint {
+        is_initialized: true,
+        ..Mint::default()
+    };
+    let mut data = vec![0; Mint::LEN];
+    Mint::pack(mint, &mut data).unwrap();
+    assert_eq!(Ok(vec![]), mint.extensions(&data));
+
+    data.resize(Mint::LEN + 64, 0);
+    let (_, extensions) = split_extensions_mut(&mut data, Mint::LEN).unwrap();
+    init_extension(extensions, ExtensionType::Pausable, PausableConfig::LEN).unwrap();
+    init_extension(extensions, ExtensionType::BurnedTotal, 8).unwrap();
+    assert_eq!(
+        Ok(vec![ExtensionType::Pausable, ExtensionType::BurnedTotal]),
+        mint.extensions(&data)
+    );
+
+    // an entry running past the end of the data is rejected
+    data.truncate(Mint::LEN + 4 + PausableConfig::LEN + 4);
+    assert_eq!(
+        Err(ProgramError::InvalidAccountData),
+        mint.extensions(&data)
+    );
+}
+
+#[test]
+fn test_add_get_and_update_extensions() {
+    let config = PausableConfig {
+        pause_authority: Pubkey::new_unique(),
+        paused: false,
+    };
+    let len = ExtensionType::get_account_len(
+        Mint::LEN,
+        &[ExtensionType::Pausable, ExtensionType::BurnedTotal],
+    );
+    assert_eq!(Mint::LEN + 4 + PausableConfig::LEN + 4 + 8, len);
+    assert_eq!(Mint::LEN, ExtensionType::get_account_len(Mint::LEN, &[]));
+
+    let mut data = vec![0; len];
+    let (_, tlv_data) = split_extensions_mut(&mut data, Mint::LEN).unwrap();
+    assert_eq!(
+        Ok(None),
+        get_u64_extension(tlv_data, ExtensionType::BurnedTotal)
+    );
+    add_extension(tlv_data, ExtensionType::Pausable, &config).unwrap();
+    add_u64_extension(tlv_data, ExtensionType::BurnedTotal, 7).unwrap();
+    assert_eq!(
+        Ok(vec![ExtensionType::Pausable, ExtensionType::BurnedTotal]),
+        get_extension_types(tlv_data)
+    );
+    assert_eq!(
+        Ok(Some(config)),
+        get_extension::<PausableConfig>(tlv_data, ExtensionType::Pausable)
+    );
+    assert_eq!(
+        Ok(Some(7)),
+        get_u64_extension(tlv_data, ExtensionType::BurnedTotal)
+    );
+
+    // overwriting keeps the entry in place
+    let paused = PausableConfig {
+        paused: true,
+        ..config
+    };
+    set_extension(tlv_data, ExtensionType::Pausable, &paused).unwrap();
+    set_u64_extension(tlv_data, ExtensionType::BurnedTotal, 9).unwrap();
+    assert_eq!(
+        Ok(Some(paused)),
+        get_extension::<PausableConfig>(tlv_data, ExtensionType::Pausable)
+    );
+    assert_eq!(
+        Ok(Some(9)),
+        get_u64_extension(tlv_data, ExtensionType::BurnedTotal)
+    );
+
+    // entries are added once and only into space that is left
+    assert_eq!(
+        Err(TokenError::AlreadyInUse.into()),
+        add_u64_extension(tlv_data, ExtensionType::BurnedTotal, 1)
+    );
+    assert_eq!(
+        Err(ProgramError::AccountDataTooSmall),
+        add_u64_extension(tlv_data, ExtensionType::CreatedSlot, 1)
+    );
+    assert_eq!(
+        Err(ProgramError::InvalidAccountData),
+        set_u64_extension(tlv_data, ExtensionType::CreatedSlot, 1)
+    );
+}
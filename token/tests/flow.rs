@@ -0,0 +1,282 @@
+//! End-to-end flows built only from the instruction builders, so that any
+//! mismatch between a builder's account order and its handler shows up
+
+mod common;
+
+use common::AccountHarness;
+use solana_program::{instruction::Instruction, program_pack::Pack, pubkey::Pubkey};
+use token::{
+    error::TokenError,
+    instruction::{
+        approve_checked, burn, close_account, initialize_account3, initialize_mint2, mint_to,
+        mint_to_checked, transfer, transfer_checked,
+    },
+    state::{Account, Mint},
+};
+
+#[test]
+fn test_mint_transfer_burn() {
+    common::set_program_stubs();
+    let mut harness = AccountHarness::default();
+    let mint_key = Pubkey::new_unique();
+    let mint_authority_key = Pubkey::new_unique();
+    let alice_key = Pubkey::new_unique();
+    let alice_account_key = Pubkey::new_unique();
+    let bob_key = Pubkey::new_unique();
+    let bob_account_key = Pubkey::new_unique();
+    harness.insert(mint_key, common::uninitialized_account(Mint::LEN));
+    harness.insert(
+        alice_account_key,
+        common::uninitialized_account(Account::LEN),
+    );
+    harness.insert(bob_account_key, common::uninitialized_account(Account::LEN));
+    let amount = |harness: &AccountHarness, key: &Pubkey| harness.unpack::<Account>(key).amount;
+    let supply = |harness: &AccountHarness| harness.unpack::<Mint>(&mint_key).supply;
+
+    harness
+        .process(&initialize_mint2(&token::id(), &mint_key, &mint_authority_key, None, 6).unwrap())
+        .unwrap();
+    for (account_key, owner_key) in [(alice_account_key, alice_key), (bob_account_key, bob_key)] {
+        harness
+            .process(
+                &initialize_account3(&token::id(), &account_key, &mint_key, &owner_key).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(owner_key, harness.unpack::<Account>(&account_key).owner);
+    }
+    assert_eq!(0, supply(&harness));
+
+    harness
+        .process(
+            &mint_to(
+                &token::id(),
+                &mint_key,
+                &alice_account_key,
+                &mint_authority_key,
+                &[],
+                1_000,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(1_000, amount(&harness, &alice_account_key));
+    assert_eq!(1_000, supply(&harness));
+
+    harness
+        .process(
+            &transfer(
+                &token::id(),
+                &alice_account_key,
+                &bob_account_key,
+                &alice_key,
+                &[],
+                400,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(600, amount(&harness, &alice_account_key));
+    assert_eq!(400, amount(&harness, &bob_account_key));
+    assert_eq!(1_000, supply(&harness));
+
+    harness
+        .process(
+            &burn(
+                &token::id(),
+                &bob_account_key,
+                &mint_key,
+                &bob_key,
+                &[],
+                150,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(600, amount(&harness, &alice_account_key));
+    assert_eq!(250, amount(&harness, &bob_account_key));
+    assert_eq!(850, supply(&harness));
+}
+
+#[test]
+fn test_burn_then_close() {
+    common::set_program_stubs();
+    let mut harness = AccountHarness::default();
+    let mint_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    harness.insert(mint_key, common::mint_account(None, 5, 0));
+    harness.insert(account_key, common::token_account(&mint_key, &owner_key, 5));
+    harness.insert(destination_key, Default::default());
+    let lamports = harness.get(&account_key).lamports;
+    let close = close_account(
+        &token::id(),
+        &account_key,
+        &destination_key,
+        &owner_key,
+        &[],
+    )
+    .unwrap();
+
+    assert!(harness.process(&close).is_err());
+    harness
+        .process(&burn(&token::id(), &account_key, &mint_key, &owner_key, &[], 5).unwrap())
+        .unwrap();
+    harness.process(&close).unwrap();
+    assert_eq!(0, harness.get(&account_key).lamports);
+    assert_eq!(lamports, harness.get(&destination_key).lamports);
+    assert_eq!(vec![0; Account::LEN], harness.get(&account_key).data);
+    assert_eq!(0, harness.unpack::<Mint>(&mint_key).supply);
+}
+
+/// Runs `checked(mint_key, decimals)` with another mint of the same decimals and
+/// with the wrong decimals, which must fail with `MintMismatch` and
+/// `MintDecimalsMismatch`, and then with the mint's own decimals, which must
+/// succeed
+fn assert_checks_mint_and_decimals(
+    harness: &mut AccountHarness,
+    mint_key: &Pubkey,
+    checked: impl Fn(&Pubkey, u8) -> Instruction,
+) {
+    let decimals = harness.unpack::<Mint>(mint_key).decimals;
+    let other_mint_key = Pubkey::new_unique();
+    harness.insert(other_mint_key, common::mint_account(None, 0, decimals));
+    for (mint_key, decimals, expected) in [
+        (
+            &other_mint_key,
+            decimals,
+            Err(TokenError::MintMismatch.into()),
+        ),
+        (
+            mint_key,
+            decimals + 1,
+            Err(TokenError::MintDecimalsMismatch.into()),
+        ),
+        (mint_key, decimals, Ok(())),
+    ] {
+        assert_eq!(
+            expected,
+            harness.process(&checked(mint_key, decimals)),
+            "{} decimals",
+            decimals
+        );
+    }
+}
+
+#[test]
+fn test_transfer_checked_validates_mint() {
+    common::set_program_stubs();
+    let mut harness = AccountHarness::default();
+    let mint_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let source_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    harness.insert(mint_key, common::mint_account(None, 100, 2));
+    harness.insert(
+        source_key,
+        common::token_account(&mint_key, &owner_key, 100),
+    );
+    harness.insert(
+        destination_key,
+        common::token_account(&mint_key, &owner_key, 0),
+    );
+
+    assert_checks_mint_and_decimals(&mut harness, &mint_key, |mint_key, decimals| {
+        transfer_checked(
+            &token::id(),
+            &source_key,
+            mint_key,
+            &destination_key,
+            &owner_key,
+            &[],
+            60,
+            decimals,
+        )
+        .unwrap()
+    });
+    assert_eq!(40, harness.unpack::<Account>(&source_key).amount);
+    assert_eq!(60, harness.unpack::<Account>(&destination_key).amount);
+}
+
+#[test]
+fn test_approve_checked_then_delegate_transfer() {
+    common::set_program_stubs();
+    let mut harness = AccountHarness::default();
+    let mint_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let delegate_key = Pubkey::new_unique();
+    let source_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    harness.insert(mint_key, common::mint_account(None, 100, 2));
+    harness.insert(
+        source_key,
+        common::token_account(&mint_key, &owner_key, 100),
+    );
+    harness.insert(
+        destination_key,
+        common::token_account(&mint_key, &owner_key, 0),
+    );
+
+    assert_checks_mint_and_decimals(&mut harness, &mint_key, |mint_key, decimals| {
+        approve_checked(
+            &token::id(),
+            &source_key,
+            mint_key,
+            &delegate_key,
+            &owner_key,
+            &[],
+            30,
+            decimals,
+        )
+        .unwrap()
+    });
+    harness
+        .process(
+            &transfer(
+                &token::id(),
+                &source_key,
+                &destination_key,
+                &delegate_key,
+                &[],
+                30,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    let source = harness.unpack::<Account>(&source_key);
+    assert_eq!(70, source.amount);
+    assert_eq!(0, source.delegated_amount);
+    assert_eq!(30, harness.unpack::<Account>(&destination_key).amount);
+}
+
+#[test]
+fn test_mint_to_checked_validates_decimals() {
+    common::set_program_stubs();
+    let mut harness = AccountHarness::default();
+    let mint_key = Pubkey::new_unique();
+    let mint_authority_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    harness.insert(
+        mint_key,
+        common::mint_account(Some(&mint_authority_key), 0, 6),
+    );
+    harness.insert(
+        account_key,
+        common::token_account(&mint_key, &Pubkey::new_unique(), 0),
+    );
+
+    assert_checks_mint_and_decimals(&mut harness, &mint_key, |mint_key, decimals| {
+        mint_to_checked(
+            &token::id(),
+            mint_key,
+            &account_key,
+            &mint_authority_key,
+            &[],
+            1_000_000,
+            decimals,
+        )
+        .unwrap()
+    });
+    assert_eq!(1_000_000, harness.unpack::<Mint>(&mint_key).supply);
+    assert_eq!(1_000_000, harness.unpack::<Account>(&account_key).amount);
+}
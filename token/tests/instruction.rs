@@ -0,0 +1,1070 @@
+mod common;
+
+use solana_program::{
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    program_option::COption,
+    pubkey::Pubkey,
+    sysvar,
+};
+use solana_sdk::account::{create_is_signer_account_infos, Account as SolanaAccount};
+use std::convert::TryFrom;
+use token::{
+    error::TokenError,
+    instruction::{
+        approve, burn, close_account, create_and_fund_mint, decode_instruction_type,
+        find_token_account_address, initialize_account2, initialize_account3, initialize_mint,
+        initialize_mint2, initialize_multisig, initialize_multisig2, is_token_instruction, mint_to,
+        revoke, set_authority, sync_native, sync_native_many, transfer, transfer_checked_many,
+        transfer_distinct, validate_transfer, AuthorityType, InstructionType, TokenInstruction,
+        MAX_SIGNERS,
+    },
+    processor::Processor,
+    state::{Account, AccountState, MAX_DECIMALS},
+};
+
+fn process_transfer(source: &Account, authority_key: &Pubkey, amount: u64) -> ProgramResult {
+    let source_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let mut source_account = common::packed_account(source);
+    let mut destination_account = common::token_account(&source.mint, &destination_key, 0);
+    let mut authority_account = SolanaAccount::default();
+    let mut accounts = [
+        (&source_key, false, &mut source_account),
+        (&destination_key, false, &mut destination_account),
+        (authority_key, true, &mut authority_account),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    Processor::process_transfer(&token::id(), &account_infos, amount, None)
+}
+
+fn assert_matches_on_chain(source: &Account, authority: &Pubkey, amount: u64) {
+    let verdict = validate_transfer(source, amount, authority).map_err(Into::into);
+    assert_eq!(process_transfer(source, authority, amount), verdict);
+}
+
+#[test]
+fn test_validate_transfer() {
+    let owner = Pubkey::new_unique();
+    let delegate = Pubkey::new_unique();
+    let mut source = Account {
+        mint: Pubkey::new_unique(),
+        owner,
+        amount: 100,
+        delegate: COption::Some(delegate),
+        delegated_amount: 30,
+        state: AccountState::Initialized,
+        ..Account::default()
+    };
+
+    assert_eq!(Ok(()), validate_transfer(&source, 100, &owner));
+    assert_eq!(
+        Err(TokenError::InsufficientFunds),
+        validate_transfer(&source, 101, &owner)
+    );
+    assert_eq!(Ok(()), validate_transfer(&source, 30, &delegate));
+    assert_eq!(
+        Err(TokenError::InsufficientFunds),
+        validate_transfer(&source, 31, &delegate)
+    );
+    assert_eq!(
+        Err(TokenError::OwnerMismatch),
+        validate_transfer(&source, 1, &Pubkey::new_unique())
+    );
+
+    source.state = AccountState::Frozen;
+    assert_eq!(
+        Err(TokenError::AccountFrozen),
+        validate_transfer(&source, 1, &owner)
+    );
+}
+
+#[test]
+fn test_validate_transfer_matches_on_chain() {
+    let owner = Pubkey::new_unique();
+    let delegate = Pubkey::new_unique();
+    let source = Account {
+        mint: Pubkey::new_unique(),
+        owner,
+        amount: 100,
+        delegate: COption::Some(delegate),
+        delegated_amount: 30,
+        state: AccountState::Initialized,
+        ..Account::default()
+    };
+
+    for amount in [0, 30, 31, 100, 101] {
+        assert_matches_on_chain(&source, &owner, amount);
+        assert_matches_on_chain(&source, &delegate, amount);
+        assert_matches_on_chain(&source, &Pubkey::new_unique(), amount);
+    }
+}
+
+#[test]
+fn test_transfer_distinct() {
+    let program_id = token::id();
+    let source = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+
+    let instruction =
+        transfer_distinct(&program_id, &source, &destination, &owner, &[], 1).unwrap();
+    assert_eq!(source, instruction.accounts[0].pubkey);
+    assert_eq!(destination, instruction.accounts[1].pubkey);
+
+    assert_eq!(
+        Err(ProgramError::InvalidArgument),
+        transfer_distinct(&program_id, &source, &source, &owner, &[], 1)
+    );
+}
+
+#[test]
+fn test_create_and_fund_mint() {
+    let mint = Pubkey::new_unique();
+    let mint_authority = Pubkey::new_unique();
+    let account = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+
+    let instructions = create_and_fund_mint(
+        &token::id(),
+        &mint,
+        &mint_authority,
+        &account,
+        &owner,
+        6,
+        1_000,
+    )
+    .unwrap();
+    assert_eq!(
+        vec![
+            InstructionType::InitializeMint,
+            InstructionType::InitializeAccount,
+            InstructionType::MintTo,
+        ],
+        instructions
+            .iter()
+            .map(|instruction| decode_instruction_type(&instruction.data).unwrap())
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        TokenInstruction::MintTo { amount: 1_000 },
+        TokenInstruction::unpack(&instructions[2].data).unwrap()
+    );
+    assert_eq!(
+        vec![
+            AccountMeta::new(mint, false),
+            AccountMeta::new(account, false),
+            AccountMeta::new_readonly(mint_authority, true),
+        ],
+        instructions[2].accounts
+    );
+
+    for (decimals, amount) in [(6, 0), (MAX_DECIMALS + 1, 1_000)].iter().cloned() {
+        assert_eq!(
+            Err(ProgramError::InvalidArgument),
+            create_and_fund_mint(
+                &token::id(),
+                &mint,
+                &mint_authority,
+                &account,
+                &owner,
+                decimals,
+                amount,
+            )
+        );
+    }
+}
+
+#[test]
+fn test_is_valid_program_id() {
+    let declared_id = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+        .parse::<Pubkey>()
+        .unwrap();
+    assert!(token::is_valid_program_id(&declared_id));
+    assert_eq!(Ok(()), token::check_program_account(&declared_id));
+
+    let random_id = Pubkey::new_unique();
+    assert!(!token::is_valid_program_id(&random_id));
+    assert_eq!(
+        Err(ProgramError::IncorrectProgramId),
+        token::check_program_account(&random_id)
+    );
+}
+
+#[test]
+fn test_find_token_account_address() {
+    let wallet = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let (address, bump) = find_token_account_address(&wallet, &mint, &token::id());
+    assert_eq!(
+        (address, bump),
+        find_token_account_address(&wallet, &mint, &token::id())
+    );
+    assert_eq!(
+        Ok(address),
+        Pubkey::create_program_address(
+            &[
+                wallet.as_ref(),
+                token::id().as_ref(),
+                mint.as_ref(),
+                &[bump]
+            ],
+            &token::id(),
+        )
+    );
+
+    let (other_address, _) =
+        find_token_account_address(&wallet, &Pubkey::new_unique(), &token::id());
+    assert_ne!(address, other_address);
+}
+
+#[test]
+fn test_is_token_instruction() {
+    let mut instruction = initialize_mint(
+        &token::id(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        None,
+        2,
+    )
+    .unwrap();
+    assert!(is_token_instruction(&instruction));
+
+    instruction.data = vec![255];
+    assert!(!is_token_instruction(&instruction));
+
+    let foreign = Instruction::new_with_bytes(Pubkey::new_unique(), &[0], vec![]);
+    assert!(!is_token_instruction(&foreign));
+}
+
+#[test]
+fn test_sync_native_many() {
+    let accounts = [
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+    let instructions = sync_native_many(&token::id(), &accounts).unwrap();
+    assert_eq!(accounts.len(), instructions.len());
+    for (account, instruction) in accounts.iter().zip(instructions) {
+        assert_eq!(sync_native(&token::id(), account).unwrap(), instruction);
+        assert_eq!(token::id(), instruction.program_id);
+        assert_eq!(
+            vec![AccountMeta::new(*account, false)],
+            instruction.accounts
+        );
+        assert_eq!(
+            TokenInstruction::SyncNative,
+            TokenInstruction::unpack(&instruction.data).unwrap()
+        );
+    }
+
+    assert_eq!(Ok(vec![]), sync_native_many(&token::id(), &[]));
+    assert_eq!(
+        Err(ProgramError::IncorrectProgramId),
+        sync_native_many(&Pubkey::new_unique(), &accounts)
+    );
+}
+
+#[test]
+fn test_transfer_checked_many() {
+    let source = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let first = Pubkey::new_unique();
+    let second = Pubkey::new_unique();
+    let destinations = [(&first, 10), (&second, 20)];
+
+    let instructions =
+        transfer_checked_many(&token::id(), &source, &mint, &destinations, &owner, &[], 6).unwrap();
+    assert_eq!(destinations.len(), instructions.len());
+    for ((destination, amount), instruction) in destinations.iter().zip(instructions) {
+        assert_eq!(
+            AccountMeta::new(**destination, false),
+            instruction.accounts[2]
+        );
+        assert_eq!(
+            TokenInstruction::TransferChecked {
+                amount: *amount,
+                decimals: 6,
+            },
+            TokenInstruction::unpack(&instruction.data).unwrap()
+        );
+    }
+
+    assert_eq!(
+        Err(ProgramError::InvalidArgument),
+        transfer_checked_many(&token::id(), &source, &mint, &[], &owner, &[], 6)
+    );
+    assert_eq!(
+        Err(ProgramError::InvalidArgument),
+        transfer_checked_many(
+            &token::id(),
+            &source,
+            &mint,
+            &[(&first, 10), (&second, 0)],
+            &owner,
+            &[],
+            6,
+        )
+    );
+}
+
+#[test]
+fn test_instruction_round_trip() {
+    let key = Pubkey::new_from_array([7; 32]);
+    let instructions = vec![
+        (
+            0,
+            TokenInstruction::InitializeMint {
+                decimals: 2,
+                mint_authority: key,
+                freeze_authority: COption::Some(key),
+            },
+        ),
+        (1, TokenInstruction::InitializeAccount),
+        (2, TokenInstruction::InitializeMultisig { m: 3 }),
+        (3, TokenInstruction::Transfer { amount: 1 }),
+        (4, TokenInstruction::Approve { amount: u64::MAX }),
+        (5, TokenInstruction::Revoke),
+        (
+            6,
+            TokenInstruction::SetAuthority {
+                authority_type: AuthorityType::FreezeAccount,
+                new_authority: COption::Some(key),
+            },
+        ),
+        (
+            6,
+            TokenInstruction::SetAuthority {
+                authority_type: AuthorityType::CloseAccount,
+                new_authority: COption::None,
+            },
+        ),
+        (7, TokenInstruction::MintTo { amount: 42 }),
+        (8, TokenInstruction::Burn { amount: 42 }),
+        (9, TokenInstruction::CloseAccount),
+        (10, TokenInstruction::FreezeAccount),
+        (11, TokenInstruction::ThawAccount),
+        (
+            12,
+            TokenInstruction::TransferChecked {
+                amount: 1,
+                decimals: 2,
+            },
+        ),
+        (
+            13,
+            TokenInstruction::ApproveChecked {
+                amount: u64::MAX,
+                decimals: 0,
+            },
+        ),
+        (
+            14,
+            TokenInstruction::MintToChecked {
+                amount: 42,
+                decimals: 9,
+            },
+        ),
+        (
+            15,
+            TokenInstruction::BurnChecked {
+                amount: 42,
+                decimals: 255,
+            },
+        ),
+        (16, TokenInstruction::InitializeAccount2 { owner: key }),
+        (17, TokenInstruction::SyncNative),
+        (18, TokenInstruction::InitializeAccount3 { owner: key }),
+        (19, TokenInstruction::InitializeMultisig2 { m: 11 }),
+        (
+            20,
+            TokenInstruction::InitializeMint2 {
+                decimals: 9,
+                mint_authority: key,
+                freeze_authority: COption::None,
+            },
+        ),
+        (21, TokenInstruction::GetAccountDataSize),
+        (22, TokenInstruction::InitializeImmutableOwner),
+        (23, TokenInstruction::AmountToUiAmount { amount: 42 }),
+        (24, TokenInstruction::UiAmountToAmount { ui_amount: "0.42" }),
+        (100, TokenInstruction::InitializeBurnedTotal),
+        (101, TokenInstruction::GetMintInfo),
+        (102, TokenInstruction::SetPause { paused: true }),
+        (
+            103,
+            TokenInstruction::InitializePausableConfig {
+                pause_authority: key,
+            },
+        ),
+        (104, TokenInstruction::WithdrawExcessLamports),
+        (105, TokenInstruction::InitializeCreatedSlot),
+        (106, TokenInstruction::BurnAndClose { amount: 42 }),
+        (107, TokenInstruction::GetAccountBalance),
+        (108, TokenInstruction::GetAccountState),
+        (
+            109,
+            TokenInstruction::TransferCheckedWithFee {
+                amount: 42,
+                decimals: 6,
+                fee: 2,
+            },
+        ),
+        (
+            110,
+            TokenInstruction::InitializeTransferFeeConfig {
+                transfer_fee_basis_points: 25,
+                maximum_fee: 1_000,
+            },
+        ),
+        (
+            111,
+            TokenInstruction::InitializeInterestBearingConfig { rate: -500 },
+        ),
+        (
+            112,
+            TokenInstruction::InitializeMintWithSupply {
+                decimals: 2,
+                mint_authority: key,
+                freeze_authority: COption::Some(key),
+                initial_supply: 42,
+            },
+        ),
+    ];
+    // one reused buffer ends up holding every instruction back to back
+    let mut buf = Vec::new();
+    let mut expected = Vec::new();
+    for (tag, instruction) in instructions {
+        let packed = instruction.pack();
+        assert_eq!(tag, packed[0]);
+        assert_eq!(instruction.packed_len(), packed.len());
+        assert_eq!(instruction, TokenInstruction::unpack(&packed).unwrap());
+
+        let start = buf.len();
+        instruction.pack_into(&mut buf);
+        assert_eq!(packed, buf[start..]);
+        expected.extend_from_slice(&packed);
+    }
+    assert_eq!(expected, buf);
+}
+
+#[test]
+fn test_packed_len() {
+    let key = Pubkey::new_from_array([7; 32]);
+    let mut instructions = vec![
+        (1, TokenInstruction::InitializeAccount),
+        (1, TokenInstruction::Revoke),
+        (1, TokenInstruction::CloseAccount),
+        (9, TokenInstruction::Transfer { amount: 1 }),
+        (9, TokenInstruction::Approve { amount: 1 }),
+        (9, TokenInstruction::MintTo { amount: 1 }),
+        (9, TokenInstruction::Burn { amount: 1 }),
+        (
+            10,
+            TokenInstruction::TransferChecked {
+                amount: 1,
+                decimals: 2,
+            },
+        ),
+        (33, TokenInstruction::InitializeAccount2 { owner: key }),
+        (
+            3,
+            TokenInstruction::SetAuthority {
+                authority_type: AuthorityType::AccountOwner,
+                new_authority: COption::None,
+            },
+        ),
+        (
+            35,
+            TokenInstruction::SetAuthority {
+                authority_type: AuthorityType::AccountOwner,
+                new_authority: COption::Some(key),
+            },
+        ),
+        (4, TokenInstruction::UiAmountToAmount { ui_amount: "4.2" }),
+    ];
+    for (freeze_authority, len) in [(COption::None, 35), (COption::Some(key), 67)] {
+        instructions.push((
+            len,
+            TokenInstruction::InitializeMint {
+                decimals: 2,
+                mint_authority: key,
+                freeze_authority,
+            },
+        ));
+        instructions.push((
+            len + 8,
+            TokenInstruction::InitializeMintWithSupply {
+                decimals: 2,
+                mint_authority: key,
+                freeze_authority,
+                initial_supply: 42,
+            },
+        ));
+        instructions.push((
+            len,
+            TokenInstruction::InitializeMint2 {
+                decimals: 2,
+                mint_authority: key,
+                freeze_authority,
+            },
+        ));
+    }
+    for (len, instruction) in instructions {
+        assert_eq!(len, instruction.packed_len(), "{:?}", instruction);
+        assert_eq!(len, instruction.pack().len(), "{:?}", instruction);
+    }
+}
+
+#[test]
+fn test_unpack_truncated_payloads() {
+    let key = Pubkey::new_from_array([7; 32]);
+    let instructions = vec![
+        TokenInstruction::InitializeMint {
+            decimals: 2,
+            mint_authority: key,
+            freeze_authority: COption::Some(key),
+        },
+        TokenInstruction::InitializeMintWithSupply {
+            decimals: 2,
+            mint_authority: key,
+            freeze_authority: COption::None,
+            initial_supply: 42,
+        },
+        TokenInstruction::InitializeMultisig { m: 1 },
+        TokenInstruction::Transfer { amount: 42 },
+        TokenInstruction::Approve { amount: 42 },
+        TokenInstruction::SetAuthority {
+            authority_type: AuthorityType::MintTokens,
+            new_authority: COption::Some(key),
+        },
+        TokenInstruction::SetAuthority {
+            authority_type: AuthorityType::FreezeAccount,
+            new_authority: COption::None,
+        },
+        TokenInstruction::MintTo { amount: 42 },
+        TokenInstruction::Burn { amount: 42 },
+        TokenInstruction::TransferChecked {
+            amount: 42,
+            decimals: 2,
+        },
+        TokenInstruction::ApproveChecked {
+            amount: 42,
+            decimals: 2,
+        },
+        TokenInstruction::MintToChecked {
+            amount: 42,
+            decimals: 2,
+        },
+        TokenInstruction::BurnChecked {
+            amount: 42,
+            decimals: 2,
+        },
+        TokenInstruction::InitializeAccount2 { owner: key },
+        TokenInstruction::InitializeAccount3 { owner: key },
+        TokenInstruction::InitializeMultisig2 { m: 1 },
+        TokenInstruction::InitializeMint2 {
+            decimals: 2,
+            mint_authority: key,
+            freeze_authority: COption::None,
+        },
+        TokenInstruction::AmountToUiAmount { amount: 42 },
+        TokenInstruction::SetPause { paused: true },
+        TokenInstruction::BurnAndClose { amount: 42 },
+        TokenInstruction::TransferCheckedWithFee {
+            amount: 42,
+            decimals: 2,
+            fee: 1,
+        },
+        TokenInstruction::InitializeTransferFeeConfig {
+            transfer_fee_basis_points: 25,
+            maximum_fee: 1_000,
+        },
+        TokenInstruction::InitializeInterestBearingConfig { rate: 500 },
+    ];
+    for instruction in instructions {
+        let packed = instruction.pack();
+        assert_eq!(Ok(instruction), TokenInstruction::unpack(&packed));
+        assert_eq!(
+            Err(TokenError::InvalidInstruction.into()),
+            TokenInstruction::unpack(&packed[..packed.len() - 1])
+        );
+        // a bare tag never carries enough payload
+        assert_eq!(
+            Err(TokenError::InvalidInstruction.into()),
+            TokenInstruction::unpack(&packed[..1])
+        );
+    }
+}
+
+#[test]
+fn test_unpack_initialize_mint_trailing_bytes() {
+    let key = Pubkey::new_from_array([7; 32]);
+    let expected = TokenInstruction::InitializeMint {
+        decimals: 2,
+        mint_authority: key,
+        freeze_authority: COption::None,
+    };
+    let packed = expected.pack();
+    // like SPL Token, anything after the freeze authority is ignored, so the
+    // initial supply can't be smuggled into an InitializeMint
+    for trailing_len in 1..=16 {
+        let mut data = packed.clone();
+        data.resize(packed.len() + trailing_len, 1);
+        assert_eq!(
+            Ok(expected.clone()),
+            TokenInstruction::unpack(&data),
+            "{}",
+            trailing_len
+        );
+    }
+    // fixed-size clients zero-pad a `None` freeze authority to a full key
+    let mut data = packed;
+    data.resize(1 + 1 + 32 + 1 + 32, 0);
+    assert_eq!(Ok(expected), TokenInstruction::unpack(&data));
+}
+
+#[test]
+fn test_unpack_every_tag() {
+    let key = Pubkey::new_from_array([7; 32]);
+    let mut amount_data = vec![0];
+    amount_data.extend_from_slice(&42u64.to_le_bytes());
+    let mut some_key = vec![1];
+    some_key.extend_from_slice(key.as_ref());
+
+    let check = |data: Vec<u8>, expected: TokenInstruction| {
+        assert_eq!(expected, TokenInstruction::unpack(&data).unwrap());
+    };
+    check(vec![1], TokenInstruction::InitializeAccount);
+    check(vec![2, 3], TokenInstruction::InitializeMultisig { m: 3 });
+    for (tag, expected) in [
+        (3, TokenInstruction::Transfer { amount: 42 }),
+        (4, TokenInstruction::Approve { amount: 42 }),
+        (7, TokenInstruction::MintTo { amount: 42 }),
+        (8, TokenInstruction::Burn { amount: 42 }),
+    ]
+    .iter()
+    .cloned()
+    {
+        amount_data[0] = tag;
+        check(amount_data.clone(), expected);
+        assert_eq!(
+            Err(TokenError::InvalidInstruction.into()),
+            TokenInstruction::unpack(&amount_data[..8])
+        );
+    }
+    let mut checked_data = amount_data.clone();
+    checked_data.push(6);
+    for (tag, expected) in [
+        (
+            12,
+            TokenInstruction::TransferChecked {
+                amount: 42,
+                decimals: 6,
+            },
+        ),
+        (
+            13,
+            TokenInstruction::ApproveChecked {
+                amount: 42,
+                decimals: 6,
+            },
+        ),
+        (
+            14,
+            TokenInstruction::MintToChecked {
+                amount: 42,
+                decimals: 6,
+            },
+        ),
+        (
+            15,
+            TokenInstruction::BurnChecked {
+                amount: 42,
+                decimals: 6,
+            },
+        ),
+    ]
+    .iter()
+    .cloned()
+    {
+        checked_data[0] = tag;
+        check(checked_data.clone(), expected);
+        assert_eq!(
+            Err(TokenError::InvalidInstruction.into()),
+            TokenInstruction::unpack(&checked_data[..9])
+        );
+    }
+    check(vec![5], TokenInstruction::Revoke);
+    check(
+        [&[6, 2][..], &some_key].concat(),
+        TokenInstruction::SetAuthority {
+            authority_type: AuthorityType::AccountOwner,
+            new_authority: COption::Some(key),
+        },
+    );
+    check(
+        vec![6, 3, 0],
+        TokenInstruction::SetAuthority {
+            authority_type: AuthorityType::CloseAccount,
+            new_authority: COption::None,
+        },
+    );
+    check(vec![9], TokenInstruction::CloseAccount);
+    check(
+        [&[16][..], key.as_ref()].concat(),
+        TokenInstruction::InitializeAccount2 { owner: key },
+    );
+    check(vec![19, 3], TokenInstruction::InitializeMultisig2 { m: 3 });
+    check(
+        [&[20, 2][..], key.as_ref(), &[0]].concat(),
+        TokenInstruction::InitializeMint2 {
+            decimals: 2,
+            mint_authority: key,
+            freeze_authority: COption::None,
+        },
+    );
+
+    for data in [
+        vec![],
+        vec![2],
+        vec![6, 4, 0],
+        vec![6, 0],
+        vec![16, 1],
+        vec![255],
+    ]
+    .iter()
+    {
+        assert_eq!(
+            Err(TokenError::InvalidInstruction.into()),
+            TokenInstruction::unpack(data)
+        );
+    }
+}
+
+#[test]
+fn test_authority_type_conversions() {
+    for (byte, authority_type) in [
+        (0, AuthorityType::MintTokens),
+        (1, AuthorityType::FreezeAccount),
+        (2, AuthorityType::AccountOwner),
+        (3, AuthorityType::CloseAccount),
+    ]
+    .iter()
+    .cloned()
+    {
+        assert_eq!(byte, u8::from(authority_type));
+        assert_eq!(Ok(authority_type), AuthorityType::try_from(byte));
+
+        let instruction = TokenInstruction::SetAuthority {
+            authority_type,
+            new_authority: COption::None,
+        };
+        assert_eq!(vec![6, byte, 0], instruction.pack());
+    }
+    assert_eq!(
+        Err(TokenError::InvalidInstruction.into()),
+        AuthorityType::try_from(4)
+    );
+    assert_eq!(
+        Err(TokenError::InvalidInstruction.into()),
+        TokenInstruction::unpack(&[6, 4, 0])
+    );
+}
+
+#[test]
+fn test_decode_instruction_type() {
+    let types = [
+        (0, InstructionType::InitializeMint),
+        (1, InstructionType::InitializeAccount),
+        (2, InstructionType::InitializeMultisig),
+        (3, InstructionType::Transfer),
+        (4, InstructionType::Approve),
+        (5, InstructionType::Revoke),
+        (6, InstructionType::SetAuthority),
+        (7, InstructionType::MintTo),
+        (8, InstructionType::Burn),
+        (9, InstructionType::CloseAccount),
+        (10, InstructionType::FreezeAccount),
+        (11, InstructionType::ThawAccount),
+        (12, InstructionType::TransferChecked),
+        (13, InstructionType::ApproveChecked),
+        (14, InstructionType::MintToChecked),
+        (15, InstructionType::BurnChecked),
+        (16, InstructionType::InitializeAccount2),
+        (17, InstructionType::SyncNative),
+        (18, InstructionType::InitializeAccount3),
+        (19, InstructionType::InitializeMultisig2),
+        (20, InstructionType::InitializeMint2),
+        (21, InstructionType::GetAccountDataSize),
+        (22, InstructionType::InitializeImmutableOwner),
+        (23, InstructionType::AmountToUiAmount),
+        (24, InstructionType::UiAmountToAmount),
+        (100, InstructionType::InitializeBurnedTotal),
+        (101, InstructionType::GetMintInfo),
+        (102, InstructionType::SetPause),
+        (103, InstructionType::InitializePausableConfig),
+        (104, InstructionType::WithdrawExcessLamports),
+        (105, InstructionType::InitializeCreatedSlot),
+        (106, InstructionType::BurnAndClose),
+        (107, InstructionType::GetAccountBalance),
+        (108, InstructionType::GetAccountState),
+        (109, InstructionType::TransferCheckedWithFee),
+        (110, InstructionType::InitializeTransferFeeConfig),
+        (111, InstructionType::InitializeInterestBearingConfig),
+        (112, InstructionType::InitializeMintWithSupply),
+    ];
+    for tag in 0..=u8::MAX {
+        let expected = types
+            .iter()
+            .find(|(known_tag, _)| *known_tag == tag)
+            .map(|(_, instruction_type)| *instruction_type)
+            .ok_or_else(|| TokenError::InvalidInstruction.into());
+        // the payload is never looked at
+        assert_eq!(expected, decode_instruction_type(&[tag, 0xff]));
+    }
+    assert_eq!(
+        Err(TokenError::InvalidInstruction.into()),
+        decode_instruction_type(&[])
+    );
+}
+
+#[test]
+fn test_rent_free_initialize_builders() {
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+
+    let instruction =
+        initialize_mint2(&token::id(), &mint_key, &owner_key, Some(&owner_key), 6).unwrap();
+    assert_eq!(
+        TokenInstruction::InitializeMint2 {
+            decimals: 6,
+            mint_authority: owner_key,
+            freeze_authority: COption::Some(owner_key),
+        }
+        .pack(),
+        instruction.data
+    );
+    assert_eq!(
+        vec![AccountMeta::new(mint_key, false)],
+        instruction.accounts
+    );
+
+    let instruction =
+        initialize_account2(&token::id(), &account_key, &mint_key, &owner_key).unwrap();
+    assert_eq!([&[16][..], owner_key.as_ref()].concat(), instruction.data);
+    assert_eq!(
+        vec![
+            AccountMeta::new(account_key, false),
+            AccountMeta::new_readonly(mint_key, false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+        ],
+        instruction.accounts
+    );
+
+    let instruction =
+        initialize_account3(&token::id(), &account_key, &mint_key, &owner_key).unwrap();
+    assert_eq!([&[18][..], owner_key.as_ref()].concat(), instruction.data);
+    assert_eq!(
+        vec![
+            AccountMeta::new(account_key, false),
+            AccountMeta::new_readonly(mint_key, false),
+        ],
+        instruction.accounts
+    );
+
+    assert_eq!(
+        Err(ProgramError::IncorrectProgramId),
+        initialize_account3(&Pubkey::new_unique(), &account_key, &mint_key, &owner_key)
+    );
+}
+
+#[test]
+fn test_initialize_multisig_builders() {
+    let multisig = Pubkey::new_unique();
+    let signer_keys = [
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+    let signers = signer_keys.iter().collect::<Vec<_>>();
+    let signer_metas = signer_keys
+        .iter()
+        .map(|key| AccountMeta::new_readonly(*key, false))
+        .collect::<Vec<_>>();
+
+    let instruction = initialize_multisig(&token::id(), &multisig, &signers, 2).unwrap();
+    let mut expected = vec![
+        AccountMeta::new(multisig, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+    expected.extend(signer_metas.iter().cloned());
+    assert_eq!(expected, instruction.accounts);
+    assert_eq!(
+        TokenInstruction::InitializeMultisig { m: 2 },
+        TokenInstruction::unpack(&instruction.data).unwrap()
+    );
+
+    let instruction = initialize_multisig2(&token::id(), &multisig, &signers, 2).unwrap();
+    let mut expected = vec![AccountMeta::new(multisig, false)];
+    expected.extend(signer_metas);
+    assert_eq!(expected, instruction.accounts);
+    assert_eq!(
+        TokenInstruction::InitializeMultisig2 { m: 2 },
+        TokenInstruction::unpack(&instruction.data).unwrap()
+    );
+
+    let too_many = [&multisig; MAX_SIGNERS + 1];
+    for (signers, m) in [
+        (&signers[..], 0),
+        (&signers[..], 4),
+        (&[][..], 0),
+        (&too_many[..], 1),
+    ]
+    .iter()
+    .cloned()
+    {
+        assert_eq!(
+            Err(ProgramError::InvalidArgument),
+            initialize_multisig(&token::id(), &multisig, signers, m)
+        );
+        assert_eq!(
+            Err(ProgramError::InvalidArgument),
+            initialize_multisig2(&token::id(), &multisig, signers, m)
+        );
+    }
+}
+
+#[test]
+fn test_authority_builders_account_metas() {
+    let account_key = Pubkey::new_unique();
+    let other_key = Pubkey::new_unique();
+    let authority_key = Pubkey::new_unique();
+    let signer_keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let signers = signer_keys.iter().collect::<Vec<_>>();
+
+    let build = |signers: &[&Pubkey]| {
+        vec![
+            (
+                transfer(
+                    &token::id(),
+                    &account_key,
+                    &other_key,
+                    &authority_key,
+                    signers,
+                    1,
+                )
+                .unwrap(),
+                TokenInstruction::Transfer { amount: 1 },
+                vec![
+                    AccountMeta::new(account_key, false),
+                    AccountMeta::new(other_key, false),
+                ],
+            ),
+            (
+                approve(
+                    &token::id(),
+                    &account_key,
+                    &other_key,
+                    &authority_key,
+                    signers,
+                    2,
+                )
+                .unwrap(),
+                TokenInstruction::Approve { amount: 2 },
+                vec![
+                    AccountMeta::new(account_key, false),
+                    AccountMeta::new_readonly(other_key, false),
+                ],
+            ),
+            (
+                revoke(&token::id(), &account_key, &authority_key, signers).unwrap(),
+                TokenInstruction::Revoke,
+                vec![AccountMeta::new(account_key, false)],
+            ),
+            (
+                set_authority(
+                    &token::id(),
+                    &account_key,
+                    Some(&other_key),
+                    AuthorityType::CloseAccount,
+                    &authority_key,
+                    signers,
+                )
+                .unwrap(),
+                TokenInstruction::SetAuthority {
+                    authority_type: AuthorityType::CloseAccount,
+                    new_authority: COption::Some(other_key),
+                },
+                vec![AccountMeta::new(account_key, false)],
+            ),
+            (
+                mint_to(
+                    &token::id(),
+                    &other_key,
+                    &account_key,
+                    &authority_key,
+                    signers,
+                    3,
+                )
+                .unwrap(),
+                TokenInstruction::MintTo { amount: 3 },
+                vec![
+                    AccountMeta::new(other_key, false),
+                    AccountMeta::new(account_key, false),
+                ],
+            ),
+            (
+                burn(
+                    &token::id(),
+                    &account_key,
+                    &other_key,
+                    &authority_key,
+                    signers,
+                    4,
+                )
+                .unwrap(),
+                TokenInstruction::Burn { amount: 4 },
+                vec![
+                    AccountMeta::new(account_key, false),
+                    AccountMeta::new(other_key, false),
+                ],
+            ),
+            (
+                close_account(
+                    &token::id(),
+                    &account_key,
+                    &other_key,
+                    &authority_key,
+                    signers,
+                )
+                .unwrap(),
+                TokenInstruction::CloseAccount,
+                vec![
+                    AccountMeta::new(account_key, false),
+                    AccountMeta::new(other_key, false),
+                ],
+            ),
+        ]
+    };
+
+    // a single-signer authority signs itself
+    for (instruction, expected, mut accounts) in build(&[]) {
+        accounts.push(AccountMeta::new_readonly(authority_key, true));
+        assert_eq!(expected.pack(), instruction.data);
+        assert_eq!(accounts, instruction.accounts);
+    }
+
+    // a multisig authority is followed by its signers
+    for (instruction, expected, mut accounts) in build(&signers) {
+        accounts.push(AccountMeta::new_readonly(authority_key, false));
+        accounts.push(AccountMeta::new_readonly(signer_keys[0], true));
+        accounts.push(AccountMeta::new_readonly(signer_keys[1], true));
+        assert_eq!(expected.pack(), instruction.data);
+        assert_eq!(accounts, instruction.accounts);
+    }
+}
@@ -0,0 +1,20 @@
+use solana_program::pubkey::Pubkey;
+use std::str::FromStr;
+use token::native_mint;
+
+#[test]
+fn test_native_mint_id() {
+    assert_eq!(
+        Pubkey::from_str("So11111111111111111111111111111111111111112").unwrap(),
+        native_mint::id()
+    );
+    assert_eq!(native_mint::ID, native_mint::id());
+    assert_eq!(9, native_mint::DECIMALS);
+}
+
+#[test]
+fn test_is_native_mint() {
+    assert!(native_mint::is_native_mint(&native_mint::id()));
+    assert!(!native_mint::is_native_mint(&token::id()));
+    assert!(!native_mint::is_native_mint(&Pubkey::new_unique()));
+}
@@ -0,0 +1,23 @@
+//! Builds only with `--features no-entrypoint`, the way downstream programs
+//! depend on the token program for cross-program invocation. Run it with
+//! `cargo test-no-entrypoint`.
+
+#![cfg(feature = "no-entrypoint")]
+
+use token::{instruction::transfer, solana_program::pubkey::Pubkey};
+
+#[test]
+fn test_library_usable_without_entrypoint() {
+    let owner = Pubkey::new_unique();
+    let instruction = transfer(
+        &token::id(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &owner,
+        &[],
+        1,
+    )
+    .unwrap();
+    assert_eq!(token::id(), instruction.program_id);
+    assert!(token::check_program_account(&token::id()).is_ok());
+}
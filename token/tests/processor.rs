@@ -0,0 +1,3868 @@
+mod common;
+
+use solana_program::{
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar,
+};
+use solana_sdk::account::{create_is_signer_account_infos, Account as SolanaAccount};
+use std::convert::TryInto;
+use token::{
+    error::TokenError,
+    extension::{
+        get_extension, get_extension_types, get_u64_extension, init_extension, split_extensions,
+        split_extensions_mut, ExtensionType, PausableConfig, SECONDS_PER_YEAR,
+    },
+    instruction::{
+        amount_to_ui_amount, approve, approve_checked, burn_and_close, burn_checked,
+        freeze_account, get_account_balance, get_account_data_size, get_account_state,
+        get_mint_info, initialize_account, initialize_account2, initialize_account3,
+        initialize_burned_total, initialize_created_slot, initialize_immutable_owner,
+        initialize_interest_bearing_config, initialize_mint, initialize_mint2,
+        initialize_mint_with_supply, initialize_multisig, initialize_pausable_config,
+        initialize_transfer_fee_config, mint_to, mint_to_checked, revoke, set_authority, set_pause,
+        sync_native, thaw_account, transfer, transfer_checked, transfer_checked_with_fee,
+        ui_amount_to_amount, withdraw_excess_lamports, AuthorityType, TokenInstruction,
+    },
+    native_mint,
+    processor::Processor,
+    state::{Account, AccountState, Mint, Multisig, ACCOUNT_VERSION, MAX_DECIMALS},
+};
+
+fn close_account_to(destination_key: &Pubkey) -> Result<(), ProgramError> {
+    let program_id = token::id();
+    let account_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut account = common::token_account(&Pubkey::new_unique(), &owner_key, 0);
+    let mut destination = SolanaAccount::default();
+    let mut owner = SolanaAccount::default();
+    let mut accounts = [
+        (&account_key, false, &mut account),
+        (destination_key, false, &mut destination),
+        (&owner_key, true, &mut owner),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    Processor::process_close_account(&program_id, &account_infos)
+}
+
+#[test]
+fn test_close_account_rejects_sysvar_destination() {
+    assert_eq!(
+        Err(ProgramError::InvalidArgument),
+        close_account_to(&sysvar::rent::id())
+    );
+    assert_eq!(
+        Err(ProgramError::InvalidArgument),
+        close_account_to(&sysvar::clock::id())
+    );
+}
+
+#[test]
+fn test_close_account_rejects_program_id_destination() {
+    assert_eq!(
+        Err(ProgramError::InvalidArgument),
+        close_account_to(&token::id())
+    );
+    assert_eq!(Ok(()), close_account_to(&Pubkey::new_unique()));
+}
+
+#[test]
+fn test_aliased_accounts_are_rejected() {
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let source_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+
+    // transfer_checked with mint == source
+    let mut source = common::token_account(&mint_key, &owner_key, 10);
+    let mut aliased_mint = common::mint_account(Some(&owner_key), 10, 2);
+    let mut destination = common::token_account(&mint_key, &destination_key, 0);
+    let mut owner = SolanaAccount::default();
+    let mut accounts = [
+        (&source_key, false, &mut source),
+        (&source_key, false, &mut aliased_mint),
+        (&destination_key, false, &mut destination),
+        (&owner_key, true, &mut owner),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    assert_eq!(
+        Err(ProgramError::AccountBorrowFailed),
+        Processor::process_transfer(&program_id, &account_infos, 1, Some(2))
+    );
+
+    // transfer with source == authority
+    let mut source = common::token_account(&mint_key, &source_key, 10);
+    let mut destination = common::token_account(&mint_key, &destination_key, 0);
+    let mut aliased_authority = SolanaAccount::default();
+    let mut accounts = [
+        (&source_key, false, &mut source),
+        (&destination_key, false, &mut destination),
+        (&source_key, true, &mut aliased_authority),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    assert_eq!(
+        Err(ProgramError::AccountBorrowFailed),
+        Processor::process_transfer(&program_id, &account_infos, 1, None)
+    );
+
+    // mint_to with mint == destination
+    let mut mint = common::mint_account(Some(&owner_key), 0, 2);
+    let mut aliased_destination = common::token_account(&mint_key, &owner_key, 0);
+    let mut owner = SolanaAccount::default();
+    let mut accounts = [
+        (&mint_key, false, &mut mint),
+        (&mint_key, false, &mut aliased_destination),
+        (&owner_key, true, &mut owner),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    assert_eq!(
+        Err(ProgramError::AccountBorrowFailed),
+        Processor::process_mint_to(&program_id, &account_infos, 1, None)
+    );
+
+    // burn with mint == source
+    let mut source = common::token_account(&mint_key, &owner_key, 10);
+    let mut aliased_mint = common::mint_account(Some(&owner_key), 10, 2);
+    let mut owner = SolanaAccount::default();
+    let mut accounts = [
+        (&source_key, false, &mut source),
+        (&source_key, false, &mut aliased_mint),
+        (&owner_key, true, &mut owner),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    assert_eq!(
+        Err(ProgramError::AccountBorrowFailed),
+        Processor::process_burn(&program_id, &account_infos, 1, None)
+    );
+
+    // burn with source == authority
+    let mut source = common::token_account(&mint_key, &source_key, 10);
+    let mut mint = common::mint_account(Some(&owner_key), 10, 2);
+    let mut aliased_authority = SolanaAccount::default();
+    let mut accounts = [
+        (&source_key, false, &mut source),
+        (&mint_key, false, &mut mint),
+        (&source_key, true, &mut aliased_authority),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    assert_eq!(
+        Err(ProgramError::AccountBorrowFailed),
+        Processor::process_burn(&program_id, &account_infos, 1, None)
+    );
+}
+
+fn burn(mint: &mut SolanaAccount, amount: u64) -> Result<(), ProgramError> {
+    let mint_key = Pubkey::new_unique();
+    let source_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut source = common::token_account(&mint_key, &owner_key, 100);
+    let mut owner = SolanaAccount::default();
+    let mut accounts = [
+        (&source_key, false, &mut source),
+        (&mint_key, false, mint),
+        (&owner_key, true, &mut owner),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    Processor::process_burn(&token::id(), &account_infos, amount, None)?;
+    let source = Account::unpack(&account_infos[0].data.borrow())?;
+    assert_eq!(100 - amount, source.amount);
+    Ok(())
+}
+
+#[test]
+fn test_burn_updates_burned_total() {
+    let mut mint = common::mint_account(None, 100, 2);
+    mint.data.resize(Mint::LEN + 12, 0);
+    let (_, extensions) = split_extensions_mut(&mut mint.data, Mint::LEN).unwrap();
+    init_extension(extensions, ExtensionType::BurnedTotal, 8).unwrap();
+
+    burn(&mut mint, 30).unwrap();
+    burn(&mut mint, 12).unwrap();
+
+    let (base, extensions) = split_extensions(&mint.data, Mint::LEN).unwrap();
+    assert_eq!(58, Mint::unpack(base).unwrap().supply);
+    assert_eq!(
+        Some(42),
+        get_u64_extension(extensions, ExtensionType::BurnedTotal).unwrap()
+    );
+}
+
+#[test]
+fn test_burn_without_burned_total() {
+    let mut mint = common::mint_account(None, 100, 2);
+    burn(&mut mint, 30).unwrap();
+
+    let (base, extensions) = split_extensions(&mint.data, Mint::LEN).unwrap();
+    assert_eq!(70, Mint::unpack(base).unwrap().supply);
+    assert_eq!(
+        None,
+        get_u64_extension(extensions, ExtensionType::BurnedTotal).unwrap()
+    );
+}
+
+#[test]
+fn test_initialize_burned_total() {
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let mut mint = SolanaAccount::new(0, Mint::LEN + 12, &program_id);
+    let instruction = initialize_burned_total(&program_id, &mint_key).unwrap();
+
+    let mut accounts = [(&mint_key, false, &mut mint)];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    Processor::process(&program_id, &account_infos, &instruction.data).unwrap();
+    drop(account_infos);
+    let (_, extensions) = split_extensions(&mint.data, Mint::LEN).unwrap();
+    assert_eq!(
+        Some(0),
+        get_u64_extension(extensions, ExtensionType::BurnedTotal).unwrap()
+    );
+
+    // the extension can only be added once, and only before the mint is initialized
+    let mut accounts = [(&mint_key, false, &mut mint)];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    assert_eq!(
+        Err(TokenError::AlreadyInUse.into()),
+        Processor::process(&program_id, &account_infos, &instruction.data)
+    );
+    let mut mint = common::mint_account(None, 0, 2);
+    mint.data.resize(Mint::LEN + 12, 0);
+    let mut accounts = [(&mint_key, false, &mut mint)];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    assert_eq!(
+        Err(TokenError::AlreadyInUse.into()),
+        Processor::process(&program_id, &account_infos, &instruction.data)
+    );
+
+    // without room for the extension
+    let mut mint = SolanaAccount::new(0, Mint::LEN, &program_id);
+    let mut accounts = [(&mint_key, false, &mut mint)];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    assert_eq!(
+        Err(ProgramError::AccountDataTooSmall),
+        Processor::process(&program_id, &account_infos, &instruction.data)
+    );
+
+    // nor can a mint grow to a token account's length, as the two are told
+    // apart by length
+    let mut mint = SolanaAccount::new(0, Account::LEN, &program_id);
+    let mut accounts = [(&mint_key, false, &mut mint)];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    assert_eq!(
+        Err(TokenError::InvalidMint.into()),
+        Processor::process(&program_id, &account_infos, &instruction.data)
+    );
+}
+
+#[test]
+fn test_get_mint_info() {
+    common::set_program_stubs();
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let mint_info = |mint: &mut SolanaAccount| {
+        let instruction = get_mint_info(&program_id, &mint_key).unwrap();
+        let mut accounts = [(&mint_key, false, mint)];
+        let account_infos = create_is_signer_account_infos(&mut accounts);
+        Processor::process(&program_id, &account_infos, &instruction.data)?;
+        let info = common::take_return_data();
+        assert_eq!(25, info.len());
+        Ok::<_, ProgramError>((
+            u64::from_le_bytes(info[..8].try_into().unwrap()),
+            info[8],
+            u64::from_le_bytes(info[9..17].try_into().unwrap()),
+            u64::from_le_bytes(info[17..].try_into().unwrap()),
+        ))
+    };
+
+    let mut mint = common::mint_account(None, 100, 2);
+    assert_eq!(Ok((100, 2, 0, 0)), mint_info(&mut mint));
+
+    mint.data.resize(Mint::LEN + 24, 0);
+    let (_, extensions) = split_extensions_mut(&mut mint.data, Mint::LEN).unwrap();
+    init_extension(extensions, ExtensionType::BurnedTotal, 8).unwrap();
+    init_extension(extensions, ExtensionType::CreatedSlot, 8)
+        .unwrap()
+        .copy_from_slice(&1_234u64.to_le_bytes());
+    burn(&mut mint, 30).unwrap();
+    assert_eq!(Ok((70, 2, 30, 1_234)), mint_info(&mut mint));
+
+    mint.owner = Pubkey::new_unique();
+    assert_eq!(Err(ProgramError::IncorrectProgramId), mint_info(&mut mint));
+}
+
+#[test]
+fn test_native_to_native_transfer() {
+    let program_id = token::id();
+    let native_mint_key = Pubkey::new_unique();
+    let source_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut source = common::native_account(&native_mint_key, &owner_key, 100);
+    let mut destination = common::native_account(&native_mint_key, &destination_key, 0);
+    let rent_exempt_reserve = destination.lamports;
+    // the mint is never read for native transfers, so an empty account suffices
+    let mut mint = SolanaAccount::default();
+    let mut owner = SolanaAccount::default();
+
+    let mut accounts = [
+        (&source_key, false, &mut source),
+        (&native_mint_key, false, &mut mint),
+        (&destination_key, false, &mut destination),
+        (&owner_key, true, &mut owner),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    // the decimals are still checked, against the native mint's fixed decimals
+    assert_eq!(
+        Err(TokenError::MintDecimalsMismatch.into()),
+        Processor::process_transfer(&program_id, &account_infos, 40, Some(8))
+    );
+    Processor::process_transfer(&program_id, &account_infos, 40, Some(9)).unwrap();
+    drop(account_infos);
+
+    let mut accounts = [
+        (&source_key, false, &mut source),
+        (&destination_key, false, &mut destination),
+        (&owner_key, true, &mut owner),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    Processor::process_transfer(&program_id, &account_infos, 20, None).unwrap();
+    drop(account_infos);
+
+    assert_eq!(40, Account::unpack(&source.data).unwrap().amount);
+    assert_eq!(rent_exempt_reserve + 40, source.lamports);
+    assert_eq!(60, Account::unpack(&destination.data).unwrap().amount);
+    assert_eq!(rent_exempt_reserve + 60, destination.lamports);
+}
+
+#[test]
+fn test_initialize_mint_with_supply() {
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let recipient_key = Pubkey::new_unique();
+    let recipient_owner_key = Pubkey::new_unique();
+    let mut rent_sysvar = common::rent_sysvar();
+
+    let mut mint = common::uninitialized_account(Mint::LEN);
+    let mut recipient = common::uninitialized_account(Account::LEN);
+    let mut recipient_owner = SolanaAccount::default();
+    common::do_process_instruction(
+        initialize_mint_with_supply(
+            &program_id,
+            &mint_key,
+            &owner_key,
+            None,
+            2,
+            &recipient_key,
+            &recipient_owner_key,
+            1_000,
+        )
+        .unwrap(),
+        vec![
+            &mut mint,
+            &mut rent_sysvar,
+            &mut recipient,
+            &mut recipient_owner,
+        ],
+    )
+    .unwrap();
+    let mint_state = Mint::unpack(&mint.data).unwrap();
+    assert_eq!(1_000, mint_state.supply);
+    assert_eq!(2, mint_state.decimals);
+    let recipient_state = Account::unpack(&recipient.data).unwrap();
+    assert_eq!(mint_key, recipient_state.mint);
+    assert_eq!(recipient_owner_key, recipient_state.owner);
+    assert_eq!(1_000, recipient_state.amount);
+
+    let mut mint = common::uninitialized_account(Mint::LEN);
+    common::do_process_instruction(
+        initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+        vec![&mut mint, &mut rent_sysvar],
+    )
+    .unwrap();
+    assert_eq!(0, Mint::unpack(&mint.data).unwrap().supply);
+}
+
+#[test]
+fn test_initialize_mint_with_supply_checks_recipient_mint() {
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let recipient_key = Pubkey::new_unique();
+    let mut rent_sysvar = common::rent_sysvar();
+    let mut owner = SolanaAccount::default();
+    let instruction = initialize_mint_with_supply(
+        &program_id,
+        &mint_key,
+        &owner_key,
+        None,
+        2,
+        &recipient_key,
+        &owner_key,
+        1_000,
+    )
+    .unwrap();
+
+    // an account of another mint can't receive the supply
+    let mut mint = common::uninitialized_account(Mint::LEN);
+    let mut recipient = common::token_account(&Pubkey::new_unique(), &owner_key, 0);
+    assert_eq!(
+        Err(TokenError::MintMismatch.into()),
+        common::do_process_instruction(
+            instruction.clone(),
+            vec![&mut mint, &mut rent_sysvar, &mut recipient, &mut owner],
+        )
+    );
+    assert_eq!(0, Account::unpack(&recipient.data).unwrap().amount);
+
+    // an already initialized account of the mint is credited as is
+    let mut mint = common::uninitialized_account(Mint::LEN);
+    let mut recipient = common::token_account(&mint_key, &owner_key, 0);
+    common::do_process_instruction(
+        instruction,
+        vec![&mut mint, &mut rent_sysvar, &mut recipient, &mut owner],
+    )
+    .unwrap();
+    assert_eq!(1_000, Mint::unpack(&mint.data).unwrap().supply);
+    assert_eq!(1_000, Account::unpack(&recipient.data).unwrap().amount);
+}
+
+#[test]
+fn test_native_transfer_rejects_desynced_account() {
+    let program_id = token::id();
+    let native_mint_key = Pubkey::new_unique();
+    let source_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut source = common::native_account(&native_mint_key, &owner_key, 100);
+    source.lamports -= 1;
+    let mut destination = common::native_account(&native_mint_key, &destination_key, 0);
+    let mut owner = SolanaAccount::default();
+
+    let mut accounts = [
+        (&source_key, false, &mut source),
+        (&destination_key, false, &mut destination),
+        (&owner_key, true, &mut owner),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    assert_eq!(
+        Err(TokenError::InvalidNativeAccount.into()),
+        Processor::process_transfer(&program_id, &account_infos, 10, None)
+    );
+}
+
+fn pausable_mint(mint_authority: &Pubkey, pause_authority: &Pubkey) -> SolanaAccount {
+    let mut mint = common::mint_account(Some(mint_authority), 100, 2);
+    mint.data.resize(Mint::LEN + 4 + PausableConfig::LEN, 0);
+    let (_, extensions) = split_extensions_mut(&mut mint.data, Mint::LEN).unwrap();
+    let value = init_extension(extensions, ExtensionType::Pausable, PausableConfig::LEN).unwrap();
+    PausableConfig {
+        pause_authority: *pause_authority,
+        paused: false,
+    }
+    .pack_into_slice(value);
+    mint
+}
+
+#[test]
+fn test_set_pause() {
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let source_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let pause_authority_key = Pubkey::new_unique();
+    let mut mint = pausable_mint(&owner_key, &pause_authority_key);
+    let mut source = common::token_account(&mint_key, &owner_key, 100);
+    let mut destination = common::token_account(&mint_key, &destination_key, 0);
+    let mut owner = SolanaAccount::default();
+    let mut pause_authority = SolanaAccount::default();
+
+    let mut transfer_checked = |mint: &mut SolanaAccount, amount: u64| {
+        let mut accounts = [
+            (&source_key, false, &mut source),
+            (&mint_key, false, mint),
+            (&destination_key, false, &mut destination),
+            (&owner_key, true, &mut owner),
+        ];
+        let account_infos = create_is_signer_account_infos(&mut accounts);
+        Processor::process_transfer(&program_id, &account_infos, amount, Some(2))
+    };
+
+    transfer_checked(&mut mint, 10).unwrap();
+
+    assert_eq!(
+        Err(TokenError::OwnerMismatch.into()),
+        common::do_process_instruction(
+            set_pause(&program_id, &mint_key, &owner_key, &[], true).unwrap(),
+            vec![&mut mint, &mut SolanaAccount::default()],
+        )
+    );
+    common::do_process_instruction(
+        set_pause(&program_id, &mint_key, &pause_authority_key, &[], true).unwrap(),
+        vec![&mut mint, &mut pause_authority],
+    )
+    .unwrap();
+    assert_eq!(
+        Err(TokenError::MintPaused.into()),
+        transfer_checked(&mut mint, 10)
+    );
+
+    common::do_process_instruction(
+        set_pause(&program_id, &mint_key, &pause_authority_key, &[], false).unwrap(),
+        vec![&mut mint, &mut pause_authority],
+    )
+    .unwrap();
+    transfer_checked(&mut mint, 10).unwrap();
+}
+
+#[test]
+fn test_initialize_pausable_config() {
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let pause_authority_key = Pubkey::new_unique();
+    let mut rent_sysvar = common::rent_sysvar();
+    let mut mint = common::uninitialized_account(Mint::LEN + 4 + PausableConfig::LEN);
+    let mut pause_authority = SolanaAccount::default();
+
+    common::do_process_instruction(
+        initialize_pausable_config(&program_id, &mint_key, &pause_authority_key).unwrap(),
+        vec![&mut mint],
+    )
+    .unwrap();
+    common::do_process_instruction(
+        initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+        vec![&mut mint, &mut rent_sysvar],
+    )
+    .unwrap();
+
+    // the mint is initialized, so its extensions are fixed
+    assert_eq!(
+        Err(TokenError::AlreadyInUse.into()),
+        common::do_process_instruction(
+            initialize_pausable_config(&program_id, &mint_key, &pause_authority_key).unwrap(),
+            vec![&mut mint],
+        )
+    );
+
+    common::do_process_instruction(
+        set_pause(&program_id, &mint_key, &pause_authority_key, &[], true).unwrap(),
+        vec![&mut mint, &mut pause_authority],
+    )
+    .unwrap();
+    let (_, extensions) = split_extensions(&mint.data, Mint::LEN).unwrap();
+    assert_eq!(
+        Some(PausableConfig {
+            pause_authority: pause_authority_key,
+            paused: true,
+        }),
+        get_extension(extensions, ExtensionType::Pausable).unwrap()
+    );
+}
+
+#[test]
+fn test_mint_and_burn_while_paused() {
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let pause_authority_key = Pubkey::new_unique();
+    let mut mint = pausable_mint(&owner_key, &pause_authority_key);
+    let mut pause_authority = SolanaAccount::default();
+    common::do_process_instruction(
+        set_pause(&program_id, &mint_key, &pause_authority_key, &[], true).unwrap(),
+        vec![&mut mint, &mut pause_authority],
+    )
+    .unwrap();
+
+    let mut account = common::token_account(&mint_key, &owner_key, 100);
+    let mut owner = SolanaAccount::default();
+    let mut accounts = [
+        (&mint_key, false, &mut mint),
+        (&account_key, false, &mut account),
+        (&owner_key, true, &mut owner),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    Processor::process_mint_to(&program_id, &account_infos, 50, None).unwrap();
+    drop(account_infos);
+
+    let mut accounts = [
+        (&account_key, false, &mut account),
+        (&mint_key, false, &mut mint),
+        (&owner_key, true, &mut owner),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    Processor::process_burn(&program_id, &account_infos, 30, None).unwrap();
+    drop(account_infos);
+
+    assert_eq!(120, Account::unpack(&account.data).unwrap().amount);
+    let (base, _) = split_extensions(&mint.data, Mint::LEN).unwrap();
+    assert_eq!(120, Mint::unpack(base).unwrap().supply);
+}
+
+#[test]
+fn test_multisig_rejected_as_token_account() {
+    // The first bytes of the mint key double as the multisig's m, n and
+    // initialized flag
+    let mut mint_bytes = Pubkey::new_unique().to_bytes();
+    mint_bytes[..3].copy_from_slice(&[1, 1, 1]);
+    let mint_key = Pubkey::new_from_array(mint_bytes);
+    let forged_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut destination = common::token_account(&mint_key, &owner_key, 0);
+    let mut owner = SolanaAccount::default();
+
+    // An initialized multisig whose signer keys spell out a token account of
+    // `mint_key`, in the bytes where an account's fields would be
+    let mut forged = common::uninitialized_account(Multisig::LEN);
+    common::token_account(&mint_key, &owner_key, u64::MAX)
+        .data
+        .iter()
+        .zip(forged.data.iter_mut())
+        .for_each(|(byte, forged_byte)| *forged_byte = *byte);
+    let multisig = Multisig::unpack(&forged.data).unwrap();
+    assert!(multisig.is_initialized);
+    assert!(Account::unpack_unchecked(&forged.data[..Account::LEN])
+        .unwrap()
+        .is_initialized());
+
+    assert_eq!(
+        Err(ProgramError::InvalidAccountData),
+        common::do_process_instruction(
+            transfer(
+                &token::id(),
+                &forged_key,
+                &destination_key,
+                &owner_key,
+                &[],
+                1_000,
+            )
+            .unwrap(),
+            vec![&mut forged, &mut destination, &mut owner],
+        )
+    );
+    assert_eq!(0, Account::unpack(&destination.data).unwrap().amount);
+}
+
+#[test]
+fn test_unknown_account_version_rejected() {
+    let mint_key = Pubkey::new_unique();
+    let source_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut source = common::token_account(&mint_key, &owner_key, 100);
+    source.data.resize(
+        ExtensionType::get_account_len(Account::LEN + 1, &[ExtensionType::ImmutableOwner]),
+        0,
+    );
+    let (_, extensions) = split_extensions_mut(&mut source.data, Account::LEN + 1).unwrap();
+    init_extension(extensions, ExtensionType::ImmutableOwner, 0).unwrap();
+    let mut destination = common::token_account(&mint_key, &owner_key, 0);
+    let mut owner = SolanaAccount::default();
+    let mut transfer_from_source = |source: &mut SolanaAccount| {
+        common::do_process_instruction(
+            transfer(
+                &token::id(),
+                &source_key,
+                &destination_key,
+                &owner_key,
+                &[],
+                10,
+            )
+            .unwrap(),
+            vec![source, &mut destination, &mut owner],
+        )
+    };
+
+    transfer_from_source(&mut source).unwrap();
+
+    // extended data is only read at the layout version following the base state
+    source.data[Account::LEN] = ACCOUNT_VERSION + 1;
+    assert_eq!(
+        Err(TokenError::InvalidState.into()),
+        transfer_from_source(&mut source)
+    );
+    assert_eq!(10, Account::unpack(&destination.data).unwrap().amount);
+}
+
+#[test]
+fn test_approve_exceeding_balance() {
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let source_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let delegate_key = Pubkey::new_unique();
+    let mut source = common::token_account(&mint_key, &owner_key, 100);
+    let mut destination = common::token_account(&mint_key, &owner_key, 0);
+    let mut owner = SolanaAccount::default();
+    let mut delegate = SolanaAccount::default();
+
+    // approvals are not capped by the balance
+    let mut accounts = [
+        (&source_key, false, &mut source),
+        (&delegate_key, false, &mut delegate),
+        (&owner_key, true, &mut owner),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    Processor::process_approve(&program_id, &account_infos, 500, None).unwrap();
+    drop(account_infos);
+    let account = Account::unpack(&source.data).unwrap();
+    assert_eq!(account.delegate, COption::Some(delegate_key));
+    assert_eq!(account.delegated_amount, 500);
+
+    // but the delegate can only move what the account holds
+    let mut accounts = [
+        (&source_key, false, &mut source),
+        (&destination_key, false, &mut destination),
+        (&delegate_key, true, &mut delegate),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    assert_eq!(
+        Err(TokenError::InsufficientFunds.into()),
+        Processor::process_transfer(&program_id, &account_infos, 101, None)
+    );
+    Processor::process_transfer(&program_id, &account_infos, 100, None).unwrap();
+    drop(account_infos);
+
+    let account = Account::unpack(&source.data).unwrap();
+    assert_eq!(account.amount, 0);
+    assert_eq!(account.delegated_amount, 400);
+    assert_eq!(100, Account::unpack(&destination.data).unwrap().amount);
+}
+
+#[test]
+fn test_mint_to_and_burn_adjust_raw_amounts() {
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut mint = common::mint_account(Some(&owner_key), 0, 6);
+    let mut account = common::token_account(&mint_key, &owner_key, 0);
+    let mut owner = SolanaAccount::default();
+
+    let mut accounts = [
+        (&mint_key, false, &mut mint),
+        (&account_key, false, &mut account),
+        (&owner_key, true, &mut owner),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    Processor::process_mint_to(&program_id, &account_infos, 1_000_000, Some(6)).unwrap();
+    drop(account_infos);
+
+    let mut accounts = [
+        (&account_key, false, &mut account),
+        (&mint_key, false, &mut mint),
+        (&owner_key, true, &mut owner),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    Processor::process_burn(&program_id, &account_infos, 250_000, Some(6)).unwrap();
+    drop(account_infos);
+
+    // both instructions operate on raw base units, never on decimal-scaled amounts
+    assert_eq!(750_000, Mint::unpack(&mint.data).unwrap().supply);
+    assert_eq!(750_000, Account::unpack(&account.data).unwrap().amount);
+}
+
+#[test]
+fn test_interest_bearing_ui_amounts() {
+    common::set_program_stubs();
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut mint = common::uninitialized_account(ExtensionType::get_account_len(
+        Mint::LEN,
+        &[ExtensionType::InterestBearingConfig],
+    ));
+    let mut account = common::token_account(&mint_key, &owner_key, 0);
+    let mut owner = SolanaAccount::default();
+    let year = SECONDS_PER_YEAR;
+
+    // 5% a year, accruing from the time the config is added
+    common::set_unix_timestamp(1_000);
+    common::do_process_instruction(
+        initialize_interest_bearing_config(&program_id, &mint_key, 500).unwrap(),
+        vec![&mut mint],
+    )
+    .unwrap();
+    common::do_process_instruction(
+        initialize_mint2(&program_id, &mint_key, &owner_key, None, 6).unwrap(),
+        vec![&mut mint],
+    )
+    .unwrap();
+    assert_eq!(
+        Err(TokenError::AlreadyInUse.into()),
+        common::do_process_instruction(
+            initialize_interest_bearing_config(&program_id, &mint_key, 500).unwrap(),
+            vec![&mut mint],
+        )
+    );
+    common::do_process_instruction(
+        mint_to(
+            &program_id,
+            &mint_key,
+            &account_key,
+            &owner_key,
+            &[],
+            1_000_000,
+        )
+        .unwrap(),
+        vec![&mut mint, &mut account, &mut owner],
+    )
+    .unwrap();
+
+    let to_ui_amount = |mint: &mut SolanaAccount, amount| {
+        common::do_process_instruction(
+            amount_to_ui_amount(&program_id, &mint_key, amount).unwrap(),
+            vec![mint],
+        )
+        .map(|()| String::from_utf8(common::take_return_data()).unwrap())
+    };
+    assert_eq!(Ok("1".to_string()), to_ui_amount(&mut mint, 1_000_000));
+
+    // a year later the balance reads e^0.05 times as much, but stays the same raw
+    common::set_unix_timestamp(1_000 + year);
+    assert_eq!(
+        Ok("1.051271".to_string()),
+        to_ui_amount(&mut mint, 1_000_000)
+    );
+
+    common::do_process_instruction(
+        token::instruction::burn(
+            &program_id,
+            &account_key,
+            &mint_key,
+            &owner_key,
+            &[],
+            250_000,
+        )
+        .unwrap(),
+        vec![&mut account, &mut mint, &mut owner],
+    )
+    .unwrap();
+    assert_eq!(
+        750_000,
+        Mint::unpack(&mint.data[..Mint::LEN]).unwrap().supply
+    );
+    assert_eq!(750_000, Account::unpack(&account.data).unwrap().amount);
+
+    common::set_unix_timestamp(1_000 + 2 * year);
+    assert_eq!(Ok("0.828878".to_string()), to_ui_amount(&mut mint, 750_000));
+    common::do_process_instruction(
+        ui_amount_to_amount(&program_id, &mint_key, "0.828878").unwrap(),
+        vec![&mut mint],
+    )
+    .unwrap();
+    assert_eq!(
+        750_000u64.to_le_bytes().to_vec(),
+        common::take_return_data()
+    );
+}
+
+#[test]
+fn test_wrong_length_accounts() {
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut account = common::token_account(&mint_key, &owner_key, 100);
+    let mut destination = common::token_account(&mint_key, &owner_key, 0);
+    let mut owner = SolanaAccount::default();
+
+    // a token account handed in as the mint
+    let mut not_a_mint = common::token_account(&mint_key, &owner_key, 0);
+    assert_eq!(
+        Err(TokenError::InvalidMint.into()),
+        common::do_process_instruction(
+            mint_to(&token::id(), &mint_key, &account_key, &owner_key, &[], 1).unwrap(),
+            vec![&mut not_a_mint, &mut account, &mut owner],
+        )
+    );
+    assert_eq!(
+        Err(TokenError::InvalidMint.into()),
+        common::do_process_instruction(
+            transfer_checked(
+                &token::id(),
+                &account_key,
+                &mint_key,
+                &destination_key,
+                &owner_key,
+                &[],
+                1,
+                2,
+            )
+            .unwrap(),
+            vec![&mut account, &mut not_a_mint, &mut destination, &mut owner],
+        )
+    );
+
+    // and a mint, or a truncated account, handed in as a token account
+    let mut mint = common::mint_account(Some(&owner_key), 0, 2);
+    let mut truncated = common::token_account(&mint_key, &owner_key, 0);
+    truncated.data.truncate(Account::LEN - 1);
+    let mut mint_as_account = mint.clone();
+    for not_an_account in [&mut mint_as_account, &mut truncated] {
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            common::do_process_instruction(
+                transfer(
+                    &token::id(),
+                    &account_key,
+                    &destination_key,
+                    &owner_key,
+                    &[],
+                    1,
+                )
+                .unwrap(),
+                vec![&mut account, not_an_account, &mut owner],
+            )
+        );
+    }
+    assert_eq!(
+        Err(ProgramError::InvalidAccountData),
+        common::do_process_instruction(
+            mint_to(&token::id(), &mint_key, &account_key, &owner_key, &[], 1).unwrap(),
+            vec![&mut mint, &mut truncated, &mut owner],
+        )
+    );
+    assert_eq!(100, Account::unpack(&account.data).unwrap().amount);
+}
+
+#[test]
+fn test_mint_to_checks_destination_before_authority() {
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let impostor_key = Pubkey::new_unique();
+    let mut mint = common::mint_account(Some(&owner_key), 0, 6);
+    let mut frozen = common::packed_account(&Account {
+        mint: mint_key,
+        owner: owner_key,
+        state: AccountState::Frozen,
+        ..Account::default()
+    });
+    let mut native = common::native_account(&mint_key, &owner_key, 0);
+    let mut impostor = SolanaAccount::default();
+
+    for (destination, expected) in [
+        (&mut frozen, TokenError::AccountFrozen),
+        (&mut native, TokenError::NativeNotSupported),
+    ] {
+        // neither the wrong authority nor the missing signature is reported
+        let mut accounts = [
+            (&mint_key, false, &mut mint),
+            (&account_key, false, destination),
+            (&impostor_key, false, &mut impostor),
+        ];
+        let account_infos = create_is_signer_account_infos(&mut accounts);
+        assert_eq!(
+            Err(expected.into()),
+            Processor::process_mint_to(&program_id, &account_infos, 10, None)
+        );
+    }
+    assert_eq!(0, Mint::unpack(&mint.data).unwrap().supply);
+}
+
+#[cfg(feature = "verbose-logs")]
+#[test]
+fn test_unchecked_mint_to_logs_decimals() {
+    common::set_program_stubs();
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut mint = common::mint_account(Some(&owner_key), 0, 6);
+    let mut account = common::token_account(&mint_key, &owner_key, 0);
+    let mut owner = SolanaAccount::default();
+
+    let mut accounts = [
+        (&mint_key, false, &mut mint),
+        (&account_key, false, &mut account),
+        (&owner_key, true, &mut owner),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    common::take_logs();
+    Processor::process_mint_to(&token::id(), &account_infos, 5, None).unwrap();
+    let account_key = account_key.to_string();
+    let minted = format!(
+        "Minted 5 to {}..{}",
+        &account_key[..4],
+        &account_key[account_key.len() - 4..]
+    );
+    assert_eq!(
+        vec![
+            "Minting 5 with unchecked decimals (6)".to_string(),
+            minted.clone()
+        ],
+        common::take_logs()
+    );
+    Processor::process_mint_to(&token::id(), &account_infos, 5, Some(6)).unwrap();
+    assert_eq!(vec![minted], common::take_logs());
+}
+
+#[cfg(feature = "verbose-logs")]
+#[test]
+fn test_verbose_logs_of_moved_amounts() {
+    common::set_program_stubs();
+    let mint_key = Pubkey::new_unique();
+    let source_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut mint = common::mint_account(Some(&owner_key), 0, 2);
+    let mut source = common::token_account(&mint_key, &owner_key, 0);
+    let mut destination = common::token_account(&mint_key, &owner_key, 0);
+    let mut owner = SolanaAccount::default();
+    let short_key = |key: &Pubkey| {
+        let key = key.to_string();
+        format!("{}..{}", &key[..4], &key[key.len() - 4..])
+    };
+    common::take_logs();
+
+    common::do_process_instruction(
+        mint_to(&token::id(), &mint_key, &source_key, &owner_key, &[], 100).unwrap(),
+        vec![&mut mint, &mut source, &mut owner],
+    )
+    .unwrap();
+    common::do_process_instruction(
+        transfer(
+            &token::id(),
+            &source_key,
+            &destination_key,
+            &owner_key,
+            &[],
+            60,
+        )
+        .unwrap(),
+        vec![&mut source, &mut destination, &mut owner],
+    )
+    .unwrap();
+    common::do_process_instruction(
+        token::instruction::burn(
+            &token::id(),
+            &destination_key,
+            &mint_key,
+            &owner_key,
+            &[],
+            25,
+        )
+        .unwrap(),
+        vec![&mut destination, &mut mint, &mut owner],
+    )
+    .unwrap();
+
+    assert_eq!(
+        vec![
+            "Instruction: MintTo".to_string(),
+            "Minting 100 with unchecked decimals (2)".to_string(),
+            format!("Minted 100 to {}", short_key(&source_key)),
+            "Instruction: Transfer".to_string(),
+            format!(
+                "Transferred 60 from {} to {}",
+                short_key(&source_key),
+                short_key(&destination_key)
+            ),
+            "Instruction: Burn".to_string(),
+            format!("Burned 25 from {}", short_key(&destination_key)),
+        ],
+        common::take_logs()
+    );
+    assert_eq!(75, Mint::unpack(&mint.data).unwrap().supply);
+}
+
+fn withdraw_excess(source: &mut SolanaAccount) -> Result<u64, ProgramError> {
+    common::set_program_stubs();
+    let source_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let owner_key = Account::unpack_unchecked(&source.data)?.owner;
+    let mut destination = SolanaAccount::default();
+    let mut owner = SolanaAccount::default();
+    let instruction =
+        withdraw_excess_lamports(&token::id(), &source_key, &destination_key, &owner_key, &[])?;
+    common::do_process_instruction(instruction, vec![source, &mut destination, &mut owner])?;
+    Ok(destination.lamports)
+}
+
+#[test]
+fn test_withdraw_excess_lamports() {
+    let mint_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut source = common::token_account(&mint_key, &owner_key, 10);
+    let rent_exempt_minimum = source.lamports;
+    source.lamports += 42;
+
+    assert_eq!(Ok(42), withdraw_excess(&mut source));
+    assert_eq!(rent_exempt_minimum, source.lamports);
+    assert_eq!(10, Account::unpack(&source.data).unwrap().amount);
+
+    // nothing left to withdraw
+    assert_eq!(Ok(0), withdraw_excess(&mut source));
+}
+
+#[test]
+fn test_withdraw_excess_lamports_rejects_native_account() {
+    let native_mint_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut source = common::native_account(&native_mint_key, &owner_key, 42);
+    let lamports = source.lamports;
+
+    assert_eq!(
+        Err(TokenError::NativeNotSupported.into()),
+        withdraw_excess(&mut source)
+    );
+    assert_eq!(lamports, source.lamports);
+}
+
+#[test]
+fn test_initialize_mint() {
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let freeze_key = Pubkey::new_unique();
+    let mut rent_sysvar = common::rent_sysvar();
+
+    // not rent exempt
+    let mut mint = common::uninitialized_account(Mint::LEN);
+    mint.lamports -= 1;
+    assert_eq!(
+        Err(TokenError::NotRentExempt.into()),
+        common::do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, Some(&freeze_key), 2).unwrap(),
+            vec![&mut mint, &mut rent_sysvar],
+        )
+    );
+    assert_eq!(vec![0; Mint::LEN], mint.data);
+
+    mint.lamports += 1;
+    common::do_process_instruction(
+        initialize_mint(&program_id, &mint_key, &owner_key, Some(&freeze_key), 2).unwrap(),
+        vec![&mut mint, &mut rent_sysvar],
+    )
+    .unwrap();
+    assert_eq!(
+        Mint {
+            mint_authority: COption::Some(owner_key),
+            supply: 0,
+            decimals: 2,
+            is_initialized: true,
+            freeze_authority: COption::Some(freeze_key),
+        },
+        Mint::unpack(&mint.data).unwrap()
+    );
+
+    // already initialized
+    assert_eq!(
+        Err(TokenError::AlreadyInUse.into()),
+        common::do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 9).unwrap(),
+            vec![&mut mint, &mut rent_sysvar],
+        )
+    );
+    assert_eq!(2, Mint::unpack(&mint.data).unwrap().decimals);
+}
+
+#[test]
+fn test_initialize_mint_decimals_cap() {
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut rent_sysvar = common::rent_sysvar();
+
+    let mut mint = common::uninitialized_account(Mint::LEN);
+    assert_eq!(
+        Err(TokenError::InvalidMintDecimals.into()),
+        common::do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, MAX_DECIMALS + 1).unwrap(),
+            vec![&mut mint, &mut rent_sysvar],
+        )
+    );
+    assert_eq!(vec![0; Mint::LEN], mint.data);
+
+    common::do_process_instruction(
+        initialize_mint(&program_id, &mint_key, &owner_key, None, MAX_DECIMALS).unwrap(),
+        vec![&mut mint, &mut rent_sysvar],
+    )
+    .unwrap();
+    assert_eq!(MAX_DECIMALS, Mint::unpack(&mint.data).unwrap().decimals);
+}
+
+#[test]
+fn test_initialize_mint2_reads_rent_from_sysvar_cache() {
+    common::set_program_stubs();
+    let mint_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+
+    let mut mint = common::uninitialized_account(Mint::LEN);
+    mint.lamports -= 1;
+    let mut accounts = [(&mint_key, false, &mut mint)];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    assert_eq!(
+        Err(TokenError::NotRentExempt.into()),
+        Processor::process_initialize_mint2(&account_infos, 2, owner_key, COption::None)
+    );
+    **account_infos[0].lamports.borrow_mut() += 1;
+    Processor::process_initialize_mint2(&account_infos, 2, owner_key, COption::None).unwrap();
+    assert_eq!(
+        Err(TokenError::AlreadyInUse.into()),
+        Processor::process_initialize_mint2(&account_infos, 2, owner_key, COption::None)
+    );
+    drop(account_infos);
+
+    assert!(Mint::unpack(&mint.data).unwrap().is_initialized);
+}
+
+#[cfg(feature = "verbose-logs")]
+#[test]
+fn test_close_account_logs_reclaimed_lamports() {
+    common::set_program_stubs();
+    let program_id = token::id();
+    let account_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut account = common::token_account(&Pubkey::new_unique(), &owner_key, 0);
+    let reclaimed_lamports = account.lamports;
+    let mut destination = SolanaAccount::default();
+    let mut owner = SolanaAccount::default();
+    let mut accounts = [
+        (&account_key, false, &mut account),
+        (&destination_key, false, &mut destination),
+        (&owner_key, true, &mut owner),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    common::take_logs();
+    Processor::process_close_account(&program_id, &account_infos).unwrap();
+    drop(account_infos);
+
+    assert_eq!(reclaimed_lamports, destination.lamports);
+    assert_eq!(
+        vec![format!(
+            "Reclaimed {} lamports from {} to {}",
+            reclaimed_lamports, account_key, destination_key
+        )],
+        common::take_logs()
+    );
+}
+
+fn init_token_account(
+    account: &mut SolanaAccount,
+    mint_key: &Pubkey,
+    mint: &mut SolanaAccount,
+    owner_key: &Pubkey,
+) -> Result<(), ProgramError> {
+    let account_key = Pubkey::new_unique();
+    let mut owner = SolanaAccount::default();
+    let mut rent_sysvar = common::rent_sysvar();
+    let mut accounts = [
+        (&account_key, false, account),
+        (mint_key, false, mint),
+        (owner_key, false, &mut owner),
+        (&sysvar::rent::id(), false, &mut rent_sysvar),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    Processor::process_initialize_account(&account_infos)
+}
+
+#[test]
+fn test_initialize_account() {
+    let mint_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut mint = common::mint_account(Some(&owner_key), 0, 2);
+
+    let mut account = common::uninitialized_account(Account::LEN);
+    account.lamports -= 1;
+    assert_eq!(
+        Err(TokenError::NotRentExempt.into()),
+        init_token_account(&mut account, &mint_key, &mut mint, &owner_key)
+    );
+    account.lamports += 1;
+
+    let mut uninitialized_mint = common::uninitialized_account(Mint::LEN);
+    assert_eq!(
+        Err(TokenError::InvalidMint.into()),
+        init_token_account(&mut account, &mint_key, &mut uninitialized_mint, &owner_key)
+    );
+
+    init_token_account(&mut account, &mint_key, &mut mint, &owner_key).unwrap();
+    assert_eq!(
+        Account {
+            mint: mint_key,
+            owner: owner_key,
+            state: AccountState::Initialized,
+            ..Account::default()
+        },
+        Account::unpack(&account.data).unwrap()
+    );
+
+    assert_eq!(
+        Err(TokenError::AlreadyInUse.into()),
+        init_token_account(&mut account, &mint_key, &mut mint, &owner_key)
+    );
+}
+
+#[test]
+fn test_initialize_account_for_pausable_mint() {
+    let mint_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut mint = pausable_mint(&owner_key, &owner_key);
+
+    // the base-sized account has no room for the account-side extension
+    let mut account = common::uninitialized_account(Account::LEN);
+    assert_eq!(
+        Err(ProgramError::AccountDataTooSmall),
+        init_token_account(&mut account, &mint_key, &mut mint, &owner_key)
+    );
+
+    let account_len =
+        ExtensionType::get_account_len(Account::LEN + 1, &[ExtensionType::PausableAccount]);
+    let mut account = common::uninitialized_account(account_len);
+    init_token_account(&mut account, &mint_key, &mut mint, &owner_key).unwrap();
+    let account_state = Account::unpack_versioned(&account.data).unwrap();
+    assert_eq!(mint_key, account_state.mint);
+    assert_eq!(AccountState::Initialized, account_state.state);
+    assert_eq!(
+        Ok(vec![ExtensionType::PausableAccount]),
+        get_extension_types(&account.data[Account::LEN + 1..])
+    );
+
+    // the extensions follow a version byte, which must be a known version
+    let mut account = common::uninitialized_account(account_len);
+    account.data[Account::LEN] = ACCOUNT_VERSION + 1;
+    assert_eq!(
+        Err(TokenError::InvalidState.into()),
+        init_token_account(&mut account, &mint_key, &mut mint, &owner_key)
+    );
+
+    // data of a multisig's length is never a token account, however roomy
+    let mut account = common::uninitialized_account(Multisig::LEN);
+    assert_eq!(
+        Err(ProgramError::InvalidAccountData),
+        init_token_account(&mut account, &mint_key, &mut mint, &owner_key)
+    );
+
+    // accounts of mints without such extensions keep their exact size
+    let mut mint = common::mint_account(Some(&owner_key), 0, 2);
+    let mut account = common::uninitialized_account(account_len);
+    assert_eq!(
+        Err(ProgramError::InvalidAccountData),
+        init_token_account(&mut account, &mint_key, &mut mint, &owner_key)
+    );
+}
+
+#[test]
+fn test_transfer_of_pausable_account_requires_mint() {
+    let mint_key = Pubkey::new_unique();
+    let source_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut mint = pausable_mint(&owner_key, &owner_key);
+    let mut owner = SolanaAccount::default();
+
+    let account_len =
+        ExtensionType::get_account_len(Account::LEN + 1, &[ExtensionType::PausableAccount]);
+    let mut source = common::uninitialized_account(account_len);
+    init_token_account(&mut source, &mint_key, &mut mint, &owner_key).unwrap();
+    let mut source_state = Account::unpack(&source.data[..Account::LEN]).unwrap();
+    source_state.amount = 100;
+    Account::pack(source_state, &mut source.data[..Account::LEN]).unwrap();
+    let mut destination = common::uninitialized_account(account_len);
+    init_token_account(&mut destination, &mint_key, &mut mint, &owner_key).unwrap();
+
+    // the plain transfer can't see the pause, so it is refused outright
+    assert_eq!(
+        Err(TokenError::MintRequiredForTransfer.into()),
+        common::do_process_instruction(
+            transfer(
+                &token::id(),
+                &source_key,
+                &destination_key,
+                &owner_key,
+                &[],
+                10,
+            )
+            .unwrap(),
+            vec![&mut source, &mut destination, &mut owner],
+        )
+    );
+
+    // a source without the marker doesn't get around it either, since the
+    // destination carries one
+    let mut unmarked = common::token_account(&mint_key, &owner_key, 100);
+    assert_eq!(
+        Err(TokenError::MintRequiredForTransfer.into()),
+        common::do_process_instruction(
+            transfer(
+                &token::id(),
+                &source_key,
+                &destination_key,
+                &owner_key,
+                &[],
+                10,
+            )
+            .unwrap(),
+            vec![&mut unmarked, &mut destination, &mut owner],
+        )
+    );
+
+    common::do_process_instruction(
+        transfer_checked(
+            &token::id(),
+            &source_key,
+            &mint_key,
+            &destination_key,
+            &owner_key,
+            &[],
+            10,
+            2,
+        )
+        .unwrap(),
+        vec![&mut source, &mut mint, &mut destination, &mut owner],
+    )
+    .unwrap();
+    assert_eq!(
+        10,
+        Account::unpack(&destination.data[..Account::LEN])
+            .unwrap()
+            .amount
+    );
+}
+
+#[test]
+fn test_initialize_native_account() {
+    let owner_key = Pubkey::new_unique();
+    // the native mint is never read, so it doesn't need to exist
+    let mut mint = SolanaAccount::default();
+    let mut account = common::uninitialized_account(Account::LEN);
+    let rent_exempt_reserve = account.lamports;
+    account.lamports += 42;
+
+    init_token_account(&mut account, &native_mint::id(), &mut mint, &owner_key).unwrap();
+    let account = Account::unpack(&account.data).unwrap();
+    assert!(account.is_native());
+    assert_eq!(COption::Some(rent_exempt_reserve), account.is_native);
+    assert_eq!(42, account.amount);
+    assert_eq!(native_mint::id(), account.mint);
+    assert!(native_mint::is_native_mint(&account.mint));
+
+    // any other mint yields a regular account, whatever the lamports
+    let mint_key = Pubkey::new_unique();
+    let mut mint = common::mint_account(None, 0, native_mint::DECIMALS);
+    let mut account = common::uninitialized_account(Account::LEN);
+    account.lamports += 42;
+    init_token_account(&mut account, &mint_key, &mut mint, &owner_key).unwrap();
+    let account = Account::unpack(&account.data).unwrap();
+    assert!(!account.is_native());
+    assert_eq!(0, account.amount);
+}
+
+#[test]
+fn test_process_dispatches_transfer() {
+    let mint_key = Pubkey::new_unique();
+    let source_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut source = common::token_account(&mint_key, &owner_key, 100);
+    let mut destination = common::token_account(&mint_key, &owner_key, 0);
+    let mut owner = SolanaAccount::default();
+
+    common::do_process_instruction(
+        transfer(
+            &token::id(),
+            &source_key,
+            &destination_key,
+            &owner_key,
+            &[],
+            60,
+        )
+        .unwrap(),
+        vec![&mut source, &mut destination, &mut owner],
+    )
+    .unwrap();
+
+    assert_eq!(40, Account::unpack(&source.data).unwrap().amount);
+    assert_eq!(60, Account::unpack(&destination.data).unwrap().amount);
+}
+
+#[test]
+fn test_transfer_checked() {
+    let mint_key = Pubkey::new_unique();
+    let source_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut mint = common::mint_account(None, 100, 2);
+    let mut source = common::token_account(&mint_key, &owner_key, 100);
+    let mut destination = common::token_account(&mint_key, &owner_key, 0);
+    let mut owner = SolanaAccount::default();
+
+    let mut transfer = |decimals| {
+        common::do_process_instruction(
+            transfer_checked(
+                &token::id(),
+                &source_key,
+                &mint_key,
+                &destination_key,
+                &owner_key,
+                &[],
+                60,
+                decimals,
+            )
+            .unwrap(),
+            vec![&mut source, &mut mint, &mut destination, &mut owner],
+        )
+    };
+    assert_eq!(Err(TokenError::MintDecimalsMismatch.into()), transfer(3));
+    transfer(2).unwrap();
+
+    assert_eq!(40, Account::unpack(&source.data).unwrap().amount);
+    assert_eq!(60, Account::unpack(&destination.data).unwrap().amount);
+}
+
+/// Mints zero tokens signed by the mint authority, or by an unrelated key if
+/// `wrong_authority` is set, and checks that no state changed
+fn mint_zero(wrong_authority: bool, is_signer: bool) -> Result<(), ProgramError> {
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let mint_authority_key = Pubkey::new_unique();
+    let authority_key = if wrong_authority {
+        Pubkey::new_unique()
+    } else {
+        mint_authority_key
+    };
+    let mut mint = common::mint_account(Some(&mint_authority_key), 50, 2);
+    let mut account = common::token_account(&mint_key, &Pubkey::new_unique(), 50);
+    let (expected_mint, expected_account) = (mint.clone(), account.clone());
+    let mut authority = SolanaAccount::default();
+    let mut accounts = [
+        (&mint_key, false, &mut mint),
+        (&account_key, false, &mut account),
+        (&authority_key, is_signer, &mut authority),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    let result = Processor::process_mint_to(&token::id(), &account_infos, 0, None);
+    drop(account_infos);
+    assert_eq!(expected_mint, mint);
+    assert_eq!(expected_account, account);
+    result
+}
+
+/// Burns zero tokens signed by the account owner, or by an unrelated key if
+/// `wrong_authority` is set, and checks that no state changed
+fn burn_zero(wrong_authority: bool, is_signer: bool) -> Result<(), ProgramError> {
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let authority_key = if wrong_authority {
+        Pubkey::new_unique()
+    } else {
+        owner_key
+    };
+    let mut mint = common::mint_account(None, 50, 2);
+    let mut account = common::token_account(&mint_key, &owner_key, 50);
+    let (expected_mint, expected_account) = (mint.clone(), account.clone());
+    let mut authority = SolanaAccount::default();
+    let mut accounts = [
+        (&account_key, false, &mut account),
+        (&mint_key, false, &mut mint),
+        (&authority_key, is_signer, &mut authority),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    let result = Processor::process_burn(&token::id(), &account_infos, 0, None);
+    drop(account_infos);
+    assert_eq!(expected_mint, mint);
+    assert_eq!(expected_account, account);
+    result
+}
+
+/// Transfers zero tokens signed by the source owner, or by an unrelated key if
+/// `wrong_authority` is set, and checks that no state changed
+fn transfer_zero(wrong_authority: bool, is_signer: bool) -> Result<(), ProgramError> {
+    let mint_key = Pubkey::new_unique();
+    let source_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let authority_key = if wrong_authority {
+        Pubkey::new_unique()
+    } else {
+        owner_key
+    };
+    let mut source = common::token_account(&mint_key, &owner_key, 50);
+    let mut destination = common::token_account(&mint_key, &owner_key, 0);
+    let (expected_source, expected_destination) = (source.clone(), destination.clone());
+    let mut authority = SolanaAccount::default();
+    let mut accounts = [
+        (&source_key, false, &mut source),
+        (&destination_key, false, &mut destination),
+        (&authority_key, is_signer, &mut authority),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    let result = Processor::process_transfer(&token::id(), &account_infos, 0, None);
+    drop(account_infos);
+    assert_eq!(expected_source, source);
+    assert_eq!(expected_destination, destination);
+    result
+}
+
+#[test]
+fn test_zero_amount_mint_and_burn() {
+    assert_eq!(Ok(()), mint_zero(false, true));
+    assert_eq!(
+        Err(ProgramError::MissingRequiredSignature),
+        mint_zero(false, false)
+    );
+    assert_eq!(Err(TokenError::OwnerMismatch.into()), mint_zero(true, true));
+
+    assert_eq!(Ok(()), burn_zero(false, true));
+    assert_eq!(
+        Err(ProgramError::MissingRequiredSignature),
+        burn_zero(false, false)
+    );
+    assert_eq!(Err(TokenError::OwnerMismatch.into()), burn_zero(true, true));
+
+    assert_eq!(Ok(()), transfer_zero(false, true));
+    assert_eq!(
+        Err(ProgramError::MissingRequiredSignature),
+        transfer_zero(false, false)
+    );
+    assert_eq!(
+        Err(TokenError::OwnerMismatch.into()),
+        transfer_zero(true, true)
+    );
+}
+
+#[test]
+fn test_initialize_mint_records_created_slot() {
+    common::set_program_stubs();
+    common::set_slot(1_234);
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut rent_sysvar = common::rent_sysvar();
+
+    let mut mint = common::uninitialized_account(Mint::LEN + 12);
+    common::do_process_instruction(
+        initialize_created_slot(&program_id, &mint_key).unwrap(),
+        vec![&mut mint],
+    )
+    .unwrap();
+    common::do_process_instruction(
+        initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+        vec![&mut mint, &mut rent_sysvar],
+    )
+    .unwrap();
+    common::set_slot(5_000);
+
+    let (base, extensions) = split_extensions(&mint.data, Mint::LEN).unwrap();
+    assert!(Mint::unpack(base).unwrap().is_initialized);
+    assert_eq!(
+        Some(1_234),
+        get_u64_extension(extensions, ExtensionType::CreatedSlot).unwrap()
+    );
+
+    // spare room alone doesn't record the slot
+    let mut mint = common::uninitialized_account(Mint::LEN + 12);
+    common::do_process_instruction(
+        initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+        vec![&mut mint, &mut rent_sysvar],
+    )
+    .unwrap();
+    let (_, extensions) = split_extensions(&mint.data, Mint::LEN).unwrap();
+    assert_eq!(
+        None,
+        get_u64_extension(extensions, ExtensionType::CreatedSlot).unwrap()
+    );
+    assert_eq!(
+        Err(TokenError::AlreadyInUse.into()),
+        common::do_process_instruction(
+            initialize_created_slot(&program_id, &mint_key).unwrap(),
+            vec![&mut mint],
+        )
+    );
+}
+
+#[test]
+fn test_approve() {
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let delegate_key = Pubkey::new_unique();
+    let mut mint = common::mint_account(None, 100, 2);
+    let mut account = common::token_account(&mint_key, &owner_key, 100);
+    let mut delegate = SolanaAccount::default();
+    let mut owner = SolanaAccount::default();
+
+    // wrong decimals
+    let mut accounts = [
+        (&account_key, false, &mut account),
+        (&mint_key, false, &mut mint),
+        (&delegate_key, false, &mut delegate),
+        (&owner_key, true, &mut owner),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    assert_eq!(
+        Err(TokenError::MintDecimalsMismatch.into()),
+        Processor::process_approve(&program_id, &account_infos, 30, Some(3))
+    );
+    Processor::process_approve(&program_id, &account_infos, 30, Some(2)).unwrap();
+    drop(account_infos);
+
+    let approved = Account::unpack(&account.data).unwrap();
+    assert_eq!(COption::Some(delegate_key), approved.delegate);
+    assert_eq!(30, approved.delegated_amount);
+    assert_eq!(100, approved.amount);
+
+    // only the owner may approve
+    let mut accounts = [
+        (&account_key, false, &mut account),
+        (&delegate_key, false, &mut delegate),
+        (&delegate_key, true, &mut owner),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    assert_eq!(
+        Err(TokenError::OwnerMismatch.into()),
+        Processor::process_approve(&program_id, &account_infos, 100, None)
+    );
+}
+
+#[test]
+fn test_transfer_checked_with_fee() {
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let source_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let collector_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut mint = common::mint_account(None, 100, 2);
+    let mut source = common::token_account(&mint_key, &owner_key, 100);
+    let mut destination = common::token_account(&mint_key, &Pubkey::new_unique(), 0);
+    let mut collector = common::token_account(&mint_key, &Pubkey::new_unique(), 0);
+    let mut owner = SolanaAccount::default();
+
+    let mut transfer_with_fee = |amount, fee| {
+        common::do_process_instruction(
+            transfer_checked_with_fee(
+                &program_id,
+                &source_key,
+                &mint_key,
+                &destination_key,
+                &collector_key,
+                &owner_key,
+                &[],
+                amount,
+                2,
+                fee,
+            )
+            .unwrap(),
+            vec![
+                &mut source,
+                &mut mint,
+                &mut destination,
+                &mut collector,
+                &mut owner,
+            ],
+        )
+    };
+    transfer_with_fee(40, 3).unwrap();
+    assert_eq!(
+        Err(TokenError::InsufficientFunds.into()),
+        transfer_with_fee(5, 6)
+    );
+
+    assert_eq!(60, Account::unpack(&source.data).unwrap().amount);
+    assert_eq!(37, Account::unpack(&destination.data).unwrap().amount);
+    assert_eq!(3, Account::unpack(&collector.data).unwrap().amount);
+
+    // the fee collector has to hold the same mint
+    let mut collector = common::token_account(&Pubkey::new_unique(), &owner_key, 0);
+    assert_eq!(
+        Err(TokenError::MintMismatch.into()),
+        common::do_process_instruction(
+            transfer_checked_with_fee(
+                &program_id,
+                &source_key,
+                &mint_key,
+                &destination_key,
+                &collector_key,
+                &owner_key,
+                &[],
+                10,
+                2,
+                1,
+            )
+            .unwrap(),
+            vec![
+                &mut source,
+                &mut mint,
+                &mut destination,
+                &mut collector,
+                &mut owner,
+            ],
+        )
+    );
+}
+
+#[test]
+fn test_transfer_checked_with_mint_transfer_fee() {
+    common::set_program_stubs();
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let source_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let collector_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut mint = common::uninitialized_account(ExtensionType::get_account_len(
+        Mint::LEN,
+        &[ExtensionType::TransferFeeConfig],
+    ));
+    let init_fee_config = |mint: &mut SolanaAccount, transfer_fee_basis_points| {
+        common::do_process_instruction(
+            initialize_transfer_fee_config(&program_id, &mint_key, transfer_fee_basis_points, 5)
+                .unwrap(),
+            vec![mint],
+        )
+    };
+
+    // fees above the whole amount are refused
+    assert_eq!(
+        Err(ProgramError::InvalidArgument),
+        init_fee_config(&mut mint, 10_001)
+    );
+    init_fee_config(&mut mint, 250).unwrap();
+    common::do_process_instruction(
+        initialize_mint2(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+        vec![&mut mint],
+    )
+    .unwrap();
+    assert_eq!(
+        Err(TokenError::AlreadyInUse.into()),
+        init_fee_config(&mut mint, 250)
+    );
+
+    let mut source = common::token_account(&mint_key, &owner_key, 1_000);
+    let mut destination = common::token_account(&mint_key, &Pubkey::new_unique(), 0);
+    let mut collector = common::token_account(&mint_key, &Pubkey::new_unique(), 0);
+    let mut owner = SolanaAccount::default();
+    let mut transfer_with_fee = |amount, fee| {
+        common::do_process_instruction(
+            transfer_checked_with_fee(
+                &program_id,
+                &source_key,
+                &mint_key,
+                &destination_key,
+                &collector_key,
+                &owner_key,
+                &[],
+                amount,
+                2,
+                fee,
+            )
+            .unwrap(),
+            vec![
+                &mut source,
+                &mut mint,
+                &mut destination,
+                &mut collector,
+                &mut owner,
+            ],
+        )
+    };
+
+    // 2.5% of 100 rounds up to 3
+    assert_eq!(
+        Err(TokenError::FeeMismatch.into()),
+        transfer_with_fee(100, 2)
+    );
+    transfer_with_fee(100, 3).unwrap();
+    // and the fee never exceeds the maximum
+    assert_eq!(
+        Err(TokenError::FeeMismatch.into()),
+        transfer_with_fee(400, 10)
+    );
+    transfer_with_fee(400, 5).unwrap();
+
+    assert_eq!(500, Account::unpack(&source.data).unwrap().amount);
+    assert_eq!(492, Account::unpack(&destination.data).unwrap().amount);
+    assert_eq!(8, Account::unpack(&collector.data).unwrap().amount);
+}
+
+#[test]
+fn test_transfer_of_fee_mint_requires_fee() {
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let source_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut mint = common::uninitialized_account(ExtensionType::get_account_len(
+        Mint::LEN,
+        &[ExtensionType::TransferFeeConfig],
+    ));
+    common::do_process_instruction(
+        initialize_transfer_fee_config(&program_id, &mint_key, 250, 5).unwrap(),
+        vec![&mut mint],
+    )
+    .unwrap();
+    common::do_process_instruction(
+        initialize_mint2(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+        vec![&mut mint],
+    )
+    .unwrap();
+    let mut owner = SolanaAccount::default();
+
+    let account_len =
+        ExtensionType::get_account_len(Account::LEN + 1, &[ExtensionType::TransferFeeAccount]);
+    let mut source = common::uninitialized_account(account_len);
+    init_token_account(&mut source, &mint_key, &mut mint, &owner_key).unwrap();
+    assert_eq!(
+        Ok(vec![ExtensionType::TransferFeeAccount]),
+        get_extension_types(&source.data[Account::LEN + 1..])
+    );
+    let mut source_state = Account::unpack(&source.data[..Account::LEN]).unwrap();
+    source_state.amount = 100;
+    Account::pack(source_state, &mut source.data[..Account::LEN]).unwrap();
+    let mut destination = common::uninitialized_account(account_len);
+    init_token_account(&mut destination, &mint_key, &mut mint, &owner_key).unwrap();
+
+    // neither the plain nor the checked transfer pays the fee, so both are refused
+    assert_eq!(
+        Err(TokenError::MintRequiredForTransfer.into()),
+        common::do_process_instruction(
+            transfer(
+                &program_id,
+                &source_key,
+                &destination_key,
+                &owner_key,
+                &[],
+                10,
+            )
+            .unwrap(),
+            vec![&mut source, &mut destination, &mut owner],
+        )
+    );
+    assert_eq!(
+        Err(TokenError::FeeMismatch.into()),
+        common::do_process_instruction(
+            transfer_checked(
+                &program_id,
+                &source_key,
+                &mint_key,
+                &destination_key,
+                &owner_key,
+                &[],
+                10,
+                2,
+            )
+            .unwrap(),
+            vec![&mut source, &mut mint, &mut destination, &mut owner],
+        )
+    );
+    assert_eq!(100, Account::unpack_versioned(&source.data).unwrap().amount);
+    assert_eq!(
+        0,
+        Account::unpack_versioned(&destination.data).unwrap().amount
+    );
+}
+
+#[test]
+fn test_approve_rejects_owner_as_delegate() {
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let delegate_key = Pubkey::new_unique();
+    let mut account = common::token_account(&mint_key, &owner_key, 100);
+    let mut destination = common::token_account(&mint_key, &owner_key, 0);
+    let mut owner = SolanaAccount::default();
+    let mut delegate = SolanaAccount::default();
+
+    assert_eq!(
+        Err(TokenError::InvalidDelegate.into()),
+        common::do_process_instruction(
+            approve(&program_id, &account_key, &owner_key, &owner_key, &[], 30).unwrap(),
+            vec![&mut account, &mut SolanaAccount::default(), &mut owner],
+        )
+    );
+    assert_eq!(
+        COption::None,
+        Account::unpack(&account.data).unwrap().delegate
+    );
+
+    common::do_process_instruction(
+        approve(
+            &program_id,
+            &account_key,
+            &delegate_key,
+            &owner_key,
+            &[],
+            30,
+        )
+        .unwrap(),
+        vec![&mut account, &mut delegate, &mut owner],
+    )
+    .unwrap();
+    let mut transfer_by = |authority_key: &Pubkey, authority: &mut SolanaAccount, amount| {
+        common::do_process_instruction(
+            transfer(
+                &program_id,
+                &account_key,
+                &destination_key,
+                authority_key,
+                &[],
+                amount,
+            )
+            .unwrap(),
+            vec![&mut account, &mut destination, authority],
+        )
+    };
+    // the delegate spends its allowance while the owner is not bound by it
+    transfer_by(&delegate_key, &mut delegate, 10).unwrap();
+    transfer_by(&owner_key, &mut owner, 50).unwrap();
+
+    let account = Account::unpack(&account.data).unwrap();
+    assert_eq!(40, account.amount);
+    assert_eq!(COption::Some(delegate_key), account.delegate);
+    assert_eq!(20, account.delegated_amount);
+}
+
+#[test]
+fn test_zero_approval_records_delegate() {
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let delegate_key = Pubkey::new_unique();
+    let mut account = common::token_account(&mint_key, &owner_key, 100);
+    let mut destination = common::token_account(&mint_key, &owner_key, 0);
+    let mut delegate = SolanaAccount::default();
+    let mut owner = SolanaAccount::default();
+
+    let mut accounts = [
+        (&account_key, false, &mut account),
+        (&delegate_key, false, &mut delegate),
+        (&owner_key, true, &mut owner),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    Processor::process_approve(&program_id, &account_infos, 0, None).unwrap();
+    drop(account_infos);
+
+    let approved = Account::unpack(&account.data).unwrap();
+    assert_eq!(COption::Some(delegate_key), approved.delegate);
+    assert_eq!(0, approved.delegated_amount);
+
+    assert_eq!(
+        Err(TokenError::InsufficientFunds.into()),
+        common::do_process_instruction(
+            transfer(
+                &program_id,
+                &account_key,
+                &destination_key,
+                &delegate_key,
+                &[],
+                1,
+            )
+            .unwrap(),
+            vec![&mut account, &mut destination, &mut delegate],
+        )
+    );
+    assert_eq!(100, Account::unpack(&account.data).unwrap().amount);
+}
+
+#[test]
+fn test_approve_checked_then_revoke() {
+    common::set_program_stubs();
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let delegate_key = Pubkey::new_unique();
+    let mut mint = common::mint_account(None, 100, 2);
+    let mut account = common::token_account(&mint_key, &owner_key, 100);
+    let mut delegate = SolanaAccount::default();
+    let mut owner = SolanaAccount::default();
+
+    common::do_process_instruction(
+        approve_checked(
+            &program_id,
+            &account_key,
+            &mint_key,
+            &delegate_key,
+            &owner_key,
+            &[],
+            30,
+            2,
+        )
+        .unwrap(),
+        vec![&mut account, &mut mint, &mut delegate, &mut owner],
+    )
+    .unwrap();
+    assert_eq!(
+        COption::Some(delegate_key),
+        Account::unpack(&account.data).unwrap().delegate
+    );
+
+    common::take_logs();
+    let revoke_instruction = revoke(&program_id, &account_key, &owner_key, &[]).unwrap();
+    common::do_process_instruction(revoke_instruction.clone(), vec![&mut account, &mut owner])
+        .unwrap();
+    assert_eq!(
+        vec![
+            "Instruction: Revoke".to_string(),
+            format!("Revoking delegate {} with 30 tokens approved", delegate_key),
+        ],
+        common::take_logs()
+    );
+    let revoked = Account::unpack(&account.data).unwrap();
+    assert_eq!(COption::None, revoked.delegate);
+    assert_eq!(0, revoked.delegated_amount);
+
+    // nothing left to revoke
+    let data = account.data.clone();
+    common::do_process_instruction(revoke_instruction, vec![&mut account, &mut owner]).unwrap();
+    assert_eq!(data, account.data);
+    assert_eq!(vec!["Instruction: Revoke".to_string()], common::take_logs());
+}
+
+fn close_account(
+    account: &mut SolanaAccount,
+    destination: &mut SolanaAccount,
+    authority_key: &Pubkey,
+) -> Result<(), ProgramError> {
+    let account_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let mut authority = SolanaAccount::default();
+    let mut accounts = [
+        (&account_key, false, account),
+        (&destination_key, false, destination),
+        (authority_key, true, &mut authority),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    Processor::process_close_account(&token::id(), &account_infos)
+}
+
+#[test]
+fn test_close_account() {
+    let mint_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let close_authority_key = Pubkey::new_unique();
+    let mut destination = SolanaAccount::new(10, 0, &Pubkey::new_unique());
+
+    let mut account = common::token_account(&mint_key, &owner_key, 1);
+    let lamports = account.lamports;
+    assert_eq!(
+        Err(TokenError::NonNativeHasBalance.into()),
+        close_account(&mut account, &mut destination, &owner_key)
+    );
+    assert_eq!(lamports, account.lamports);
+    assert_eq!(10, destination.lamports);
+
+    // the close authority takes precedence over the owner
+    let mut state = Account::unpack(&account.data).unwrap();
+    state.amount = 0;
+    state.close_authority = COption::Some(close_authority_key);
+    Account::pack(state, &mut account.data).unwrap();
+    assert_eq!(
+        Err(TokenError::OwnerMismatch.into()),
+        close_account(&mut account, &mut destination, &owner_key)
+    );
+
+    close_account(&mut account, &mut destination, &close_authority_key).unwrap();
+    assert_eq!(0, account.lamports);
+    assert_eq!(lamports + 10, destination.lamports);
+    assert_eq!(vec![0; Account::LEN], account.data);
+    assert!(!Account::unpack_unchecked(&account.data)
+        .unwrap()
+        .is_initialized());
+}
+
+#[test]
+fn test_close_account_authorities() {
+    let mint_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut destination = SolanaAccount::default();
+
+    // without a close authority the owner closes
+    let mut account = common::token_account(&mint_key, &owner_key, 0);
+    close_account(&mut account, &mut destination, &owner_key).unwrap();
+    assert_eq!(0, account.lamports);
+
+    // a multisig close authority is validated like any other multisig
+    let account_key = Pubkey::new_unique();
+    let multisig_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let signer_keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let mut state = Multisig {
+        m: 2,
+        n: 2,
+        is_initialized: true,
+        ..Multisig::default()
+    };
+    state.signers[..2].copy_from_slice(&signer_keys);
+    let mut multisig = common::packed_account(&state);
+    let mut account = common::packed_account(&Account {
+        mint: mint_key,
+        owner: owner_key,
+        state: AccountState::Initialized,
+        close_authority: COption::Some(multisig_key),
+        ..Account::default()
+    });
+    let mut signers = vec![SolanaAccount::default(); 2];
+    let mut close_with = |signer_keys: &[&Pubkey], account: &mut SolanaAccount| {
+        let mut accounts = vec![account, &mut destination, &mut multisig];
+        accounts.extend(signers.iter_mut().take(signer_keys.len()));
+        common::do_process_instruction(
+            token::instruction::close_account(
+                &token::id(),
+                &account_key,
+                &destination_key,
+                &multisig_key,
+                signer_keys,
+            )
+            .unwrap(),
+            accounts,
+        )
+    };
+    assert_eq!(
+        Err(ProgramError::MissingRequiredSignature),
+        close_with(&[&signer_keys[0]], &mut account)
+    );
+    close_with(&[&signer_keys[0], &signer_keys[1]], &mut account).unwrap();
+    assert_eq!(0, account.lamports);
+}
+
+#[test]
+fn test_close_native_account_with_balance() {
+    let owner_key = Pubkey::new_unique();
+    let mut account = common::native_account(&native_mint::id(), &owner_key, 42);
+    let lamports = account.lamports;
+    let mut destination = SolanaAccount::default();
+
+    close_account(&mut account, &mut destination, &owner_key).unwrap();
+    assert_eq!(lamports, destination.lamports);
+    assert_eq!(vec![0; Account::LEN], account.data);
+}
+
+#[test]
+fn test_freeze_thaw_lifecycle() {
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let freeze_authority_key = Pubkey::new_unique();
+    let mut mint = common::uninitialized_account(Mint::LEN);
+    let mut account = common::uninitialized_account(Account::LEN);
+    let mut destination = common::uninitialized_account(Account::LEN);
+    let mut owner = SolanaAccount::default();
+    let mut freeze_authority = SolanaAccount::default();
+    let mut rent_sysvar = common::rent_sysvar();
+
+    common::do_process_instruction(
+        initialize_mint(
+            &program_id,
+            &mint_key,
+            &owner_key,
+            Some(&freeze_authority_key),
+            2,
+        )
+        .unwrap(),
+        vec![&mut mint, &mut rent_sysvar],
+    )
+    .unwrap();
+    for (key, account) in [
+        (&account_key, &mut account),
+        (&destination_key, &mut destination),
+    ]
+    .iter_mut()
+    {
+        common::do_process_instruction(
+            initialize_account(&program_id, key, &mint_key, &owner_key).unwrap(),
+            vec![account, &mut mint, &mut owner, &mut rent_sysvar],
+        )
+        .unwrap();
+    }
+    common::do_process_instruction(
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(mint_key, false),
+                AccountMeta::new(account_key, false),
+                AccountMeta::new_readonly(owner_key, true),
+            ],
+            data: TokenInstruction::MintTo { amount: 100 }.pack(),
+        },
+        vec![&mut mint, &mut account, &mut owner],
+    )
+    .unwrap();
+
+    common::do_process_instruction(
+        freeze_account(
+            &program_id,
+            &account_key,
+            &mint_key,
+            &freeze_authority_key,
+            &[],
+        )
+        .unwrap(),
+        vec![&mut account, &mut mint, &mut freeze_authority],
+    )
+    .unwrap();
+    assert!(Account::unpack(&account.data).unwrap().is_frozen());
+    assert_eq!(
+        Err(TokenError::AccountFrozen.into()),
+        common::do_process_instruction(
+            transfer(
+                &program_id,
+                &account_key,
+                &destination_key,
+                &owner_key,
+                &[],
+                10
+            )
+            .unwrap(),
+            vec![&mut account, &mut destination, &mut owner],
+        )
+    );
+
+    common::do_process_instruction(
+        thaw_account(
+            &program_id,
+            &account_key,
+            &mint_key,
+            &freeze_authority_key,
+            &[],
+        )
+        .unwrap(),
+        vec![&mut account, &mut mint, &mut freeze_authority],
+    )
+    .unwrap();
+    common::do_process_instruction(
+        transfer(
+            &program_id,
+            &account_key,
+            &destination_key,
+            &owner_key,
+            &[],
+            10,
+        )
+        .unwrap(),
+        vec![&mut account, &mut destination, &mut owner],
+    )
+    .unwrap();
+    assert_eq!(90, Account::unpack(&account.data).unwrap().amount);
+    assert_eq!(10, Account::unpack(&destination.data).unwrap().amount);
+}
+
+#[test]
+fn test_burn_by_owner_and_delegate() {
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let delegate_key = Pubkey::new_unique();
+    let mut mint = common::mint_account(None, 100, 2);
+    let mut account = common::packed_account(&Account {
+        mint: mint_key,
+        owner: owner_key,
+        amount: 100,
+        delegate: COption::Some(delegate_key),
+        state: AccountState::Initialized,
+        delegated_amount: 30,
+        ..Account::default()
+    });
+    let mut owner = SolanaAccount::default();
+    let mut delegate = SolanaAccount::default();
+
+    let mut accounts = [
+        (&account_key, false, &mut account),
+        (&mint_key, false, &mut mint),
+        (&owner_key, true, &mut owner),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    Processor::process_burn(&program_id, &account_infos, 50, None).unwrap();
+    drop(account_infos);
+
+    let mut accounts = [
+        (&account_key, false, &mut account),
+        (&mint_key, false, &mut mint),
+        (&delegate_key, true, &mut delegate),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    assert_eq!(
+        Err(TokenError::InsufficientFunds.into()),
+        Processor::process_burn(&program_id, &account_infos, 31, None)
+    );
+    Processor::process_burn(&program_id, &account_infos, 20, None).unwrap();
+    drop(account_infos);
+
+    let state = Account::unpack(&account.data).unwrap();
+    assert_eq!(30, state.amount);
+    assert_eq!(10, state.delegated_amount);
+    assert_eq!(COption::Some(delegate_key), state.delegate);
+    assert_eq!(30, Mint::unpack(&mint.data).unwrap().supply);
+
+    // spending the whole allowance clears the delegate
+    let mut accounts = [
+        (&account_key, false, &mut account),
+        (&mint_key, false, &mut mint),
+        (&delegate_key, true, &mut delegate),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    Processor::process_burn(&program_id, &account_infos, 10, None).unwrap();
+    drop(account_infos);
+
+    let state = Account::unpack(&account.data).unwrap();
+    assert_eq!(20, state.amount);
+    assert_eq!(0, state.delegated_amount);
+    assert_eq!(COption::None, state.delegate);
+    assert_eq!(20, Mint::unpack(&mint.data).unwrap().supply);
+}
+
+#[test]
+fn test_process_empty_instruction_data() {
+    common::set_program_stubs();
+    common::take_logs();
+    assert_eq!(
+        Err(TokenError::InvalidInstruction.into()),
+        Processor::process(&token::id(), &[], &[])
+    );
+    assert_eq!(
+        vec!["Error: empty instruction data".to_string()],
+        common::take_logs()
+    );
+}
+
+#[test]
+fn test_process_unsupported_instruction() {
+    common::set_program_stubs();
+    common::take_logs();
+    for data in [&[31][..], &[255], &[200, 1, 2, 3]] {
+        assert_eq!(
+            Err(TokenError::InvalidInstruction.into()),
+            Processor::process(&token::id(), &[], data)
+        );
+        assert_eq!(
+            vec!["Error: unsupported instruction".to_string()],
+            common::take_logs()
+        );
+    }
+
+    // a known tag with a malformed payload is rejected without that message
+    assert_eq!(
+        Err(TokenError::InvalidInstruction.into()),
+        Processor::process(&token::id(), &[], &[3, 1])
+    );
+    assert!(common::take_logs().is_empty());
+}
+
+#[test]
+fn test_process_rejects_wrong_program_id() {
+    let mint_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut source = common::token_account(&mint_key, &owner_key, 100);
+    let mut destination = common::token_account(&mint_key, &owner_key, 0);
+    let mut owner = SolanaAccount::default();
+    let mut instruction = transfer(
+        &token::id(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &owner_key,
+        &[],
+        60,
+    )
+    .unwrap();
+    instruction.program_id = Pubkey::new_unique();
+
+    assert_eq!(
+        Err(ProgramError::IncorrectProgramId),
+        common::do_process_instruction(
+            instruction,
+            vec![&mut source, &mut destination, &mut owner],
+        )
+    );
+    assert_eq!(100, Account::unpack(&source.data).unwrap().amount);
+}
+
+fn toggle_freeze(
+    freeze: bool,
+    account: &mut SolanaAccount,
+    mint_key: &Pubkey,
+    mint: &mut SolanaAccount,
+    authority_key: &Pubkey,
+) -> Result<(), ProgramError> {
+    let account_key = Pubkey::new_unique();
+    let mut authority = SolanaAccount::default();
+    let mut accounts = [
+        (&account_key, false, account),
+        (mint_key, false, mint),
+        (authority_key, true, &mut authority),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    if freeze {
+        Processor::process_freeze_account(&token::id(), &account_infos)
+    } else {
+        Processor::process_thaw_account(&token::id(), &account_infos)
+    }
+}
+
+#[test]
+fn test_freeze_and_thaw_rejections() {
+    let mint_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let freeze_authority_key = Pubkey::new_unique();
+    let mut mint = common::packed_account(&Mint {
+        is_initialized: true,
+        freeze_authority: COption::Some(freeze_authority_key),
+        ..Mint::default()
+    });
+    let mut account = common::token_account(&mint_key, &owner_key, 10);
+
+    // only frozen accounts can be thawed
+    assert_eq!(
+        Err(TokenError::InvalidState.into()),
+        toggle_freeze(
+            false,
+            &mut account,
+            &mint_key,
+            &mut mint,
+            &freeze_authority_key
+        )
+    );
+    assert_eq!(
+        Err(TokenError::OwnerMismatch.into()),
+        toggle_freeze(true, &mut account, &mint_key, &mut mint, &owner_key)
+    );
+    assert_eq!(
+        Err(TokenError::MintMismatch.into()),
+        toggle_freeze(
+            true,
+            &mut account,
+            &Pubkey::new_unique(),
+            &mut mint,
+            &freeze_authority_key
+        )
+    );
+
+    toggle_freeze(
+        true,
+        &mut account,
+        &mint_key,
+        &mut mint,
+        &freeze_authority_key,
+    )
+    .unwrap();
+    // and only unfrozen accounts can be frozen
+    assert_eq!(
+        Err(TokenError::InvalidState.into()),
+        toggle_freeze(
+            true,
+            &mut account,
+            &mint_key,
+            &mut mint,
+            &freeze_authority_key
+        )
+    );
+    toggle_freeze(
+        false,
+        &mut account,
+        &mint_key,
+        &mut mint,
+        &freeze_authority_key,
+    )
+    .unwrap();
+    assert!(!Account::unpack(&account.data).unwrap().is_frozen());
+
+    let mut mint = common::mint_account(None, 0, 2);
+    assert_eq!(
+        Err(TokenError::MintCannotFreeze.into()),
+        toggle_freeze(
+            true,
+            &mut account,
+            &mint_key,
+            &mut mint,
+            &freeze_authority_key
+        )
+    );
+}
+
+#[test]
+fn test_burn_and_close() {
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut mint = common::mint_account(None, 100, 2);
+    let mut account = common::token_account(&mint_key, &owner_key, 100);
+    let mut destination = SolanaAccount::default();
+    let mut owner = SolanaAccount::default();
+    let account_lamports = account.lamports;
+
+    // a partial burn leaves the account and the mint untouched
+    assert_eq!(
+        Err(TokenError::NonNativeHasBalance.into()),
+        common::do_process_instruction(
+            burn_and_close(
+                &token::id(),
+                &account_key,
+                &mint_key,
+                &destination_key,
+                &owner_key,
+                &[],
+                60,
+            )
+            .unwrap(),
+            vec![&mut account, &mut mint, &mut destination, &mut owner],
+        )
+    );
+    assert_eq!(100, Account::unpack(&account.data).unwrap().amount);
+    assert_eq!(100, Mint::unpack(&mint.data).unwrap().supply);
+
+    common::do_process_instruction(
+        burn_and_close(
+            &token::id(),
+            &account_key,
+            &mint_key,
+            &destination_key,
+            &owner_key,
+            &[],
+            100,
+        )
+        .unwrap(),
+        vec![&mut account, &mut mint, &mut destination, &mut owner],
+    )
+    .unwrap();
+    assert_eq!(0, Mint::unpack(&mint.data).unwrap().supply);
+    assert_eq!(0, account.lamports);
+    assert_eq!(account_lamports, destination.lamports);
+    assert!(account.data.iter().all(|byte| *byte == 0));
+
+    // native accounts are closed with CloseAccount, their tokens aren't burned
+    let mut native = common::native_account(&native_mint::id(), &owner_key, 100);
+    let native_lamports = native.lamports;
+    let mut native_mint_account = SolanaAccount::default();
+    assert_eq!(
+        Err(TokenError::NativeNotSupported.into()),
+        common::do_process_instruction(
+            burn_and_close(
+                &token::id(),
+                &account_key,
+                &native_mint::id(),
+                &destination_key,
+                &owner_key,
+                &[],
+                100,
+            )
+            .unwrap(),
+            vec![
+                &mut native,
+                &mut native_mint_account,
+                &mut destination,
+                &mut owner
+            ],
+        )
+    );
+    assert_eq!(native_lamports, native.lamports);
+    assert_eq!(100, Account::unpack(&native.data).unwrap().amount);
+}
+
+fn init_multisig(
+    multisig: &mut SolanaAccount,
+    m: u8,
+    signer_keys: &[Pubkey],
+) -> Result<(), ProgramError> {
+    let multisig_key = Pubkey::new_unique();
+    let rent_key = sysvar::rent::id();
+    let mut rent_sysvar = common::rent_sysvar();
+    let mut signers = vec![SolanaAccount::default(); signer_keys.len()];
+    let mut accounts = vec![
+        (&multisig_key, false, multisig),
+        (&rent_key, false, &mut rent_sysvar),
+    ];
+    accounts.extend(
+        signer_keys
+            .iter()
+            .zip(signers.iter_mut())
+            .map(|(key, account)| (key, false, account)),
+    );
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    Processor::process_initialize_multisig(&account_infos, m)
+}
+
+#[test]
+fn test_initialize_multisig() {
+    let signer_keys = [
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+
+    let mut multisig = common::uninitialized_account(Multisig::LEN);
+    for m in [0, 4].iter() {
+        assert_eq!(
+            Err(TokenError::InvalidNumberOfRequiredSigners.into()),
+            init_multisig(&mut multisig, *m, &signer_keys)
+        );
+    }
+    assert_eq!(
+        Err(TokenError::InvalidNumberOfProvidedSigners.into()),
+        init_multisig(&mut multisig, 1, &[])
+    );
+    assert_eq!(
+        Err(TokenError::InvalidNumberOfProvidedSigners.into()),
+        init_multisig(&mut multisig, 1, &[Pubkey::new_unique(); 12])
+    );
+
+    init_multisig(&mut multisig, 2, &signer_keys).unwrap();
+    let state = Multisig::unpack(&multisig.data).unwrap();
+    assert_eq!(2, state.m);
+    assert_eq!(3, state.n);
+    assert_eq!(&signer_keys[..], &state.signers[..3]);
+    assert_eq!(
+        Err(TokenError::AlreadyInUse.into()),
+        init_multisig(&mut multisig, 2, &signer_keys)
+    );
+}
+
+#[test]
+fn test_sync_native() {
+    let account_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut account = common::native_account(&native_mint::id(), &owner_key, 40);
+
+    // lamports deposited directly are only counted once synced
+    account.lamports += 2_000;
+    assert_eq!(40, Account::unpack(&account.data).unwrap().amount);
+    common::do_process_instruction(
+        sync_native(&token::id(), &account_key).unwrap(),
+        vec![&mut account],
+    )
+    .unwrap();
+    assert_eq!(2_040, Account::unpack(&account.data).unwrap().amount);
+
+    account.lamports -= 1;
+    assert_eq!(
+        Err(TokenError::InvalidNativeAccount.into()),
+        common::do_process_instruction(
+            sync_native(&token::id(), &account_key).unwrap(),
+            vec![&mut account],
+        )
+    );
+
+    // lamports below the rent-exempt reserve back no tokens at all
+    account.lamports = Account::unpack(&account.data).unwrap().is_native.unwrap() - 1;
+    assert_eq!(
+        Err(TokenError::InvalidNativeAccount.into()),
+        common::do_process_instruction(
+            sync_native(&token::id(), &account_key).unwrap(),
+            vec![&mut account],
+        )
+    );
+
+    let mut account = common::token_account(&Pubkey::new_unique(), &owner_key, 40);
+    assert_eq!(
+        Err(TokenError::NonNativeNotSupported.into()),
+        common::do_process_instruction(
+            sync_native(&token::id(), &account_key).unwrap(),
+            vec![&mut account],
+        )
+    );
+}
+
+#[test]
+fn test_amount_to_ui_amount_round_trip() {
+    common::set_program_stubs();
+    let mint_key = Pubkey::new_unique();
+
+    let to_ui_amount = |mint: &mut SolanaAccount, amount| {
+        common::do_process_instruction(
+            amount_to_ui_amount(&token::id(), &mint_key, amount).unwrap(),
+            vec![mint],
+        )
+        .map(|()| String::from_utf8(common::take_return_data()).unwrap())
+    };
+    let mut mint = common::mint_account(None, 0, 0);
+    assert_eq!(Ok("0".to_string()), to_ui_amount(&mut mint, 0));
+    assert_eq!(Ok("1230".to_string()), to_ui_amount(&mut mint, 1_230));
+    let mut mint = common::mint_account(None, 0, 9);
+    assert_eq!(Ok("0".to_string()), to_ui_amount(&mut mint, 0));
+    assert_eq!(Ok("0.000000001".to_string()), to_ui_amount(&mut mint, 1));
+    assert_eq!(
+        Ok("1.23".to_string()),
+        to_ui_amount(&mut mint, 1_230_000_000)
+    );
+    assert_eq!(
+        Ok("18446744073.709551615".to_string()),
+        to_ui_amount(&mut mint, u64::MAX)
+    );
+    let mut mint = common::mint_account(None, 0, MAX_DECIMALS);
+    assert_eq!(
+        Ok("0.0000000000000000001".to_string()),
+        to_ui_amount(&mut mint, 1)
+    );
+    assert_eq!(
+        Ok("1.8446744073709551615".to_string()),
+        to_ui_amount(&mut mint, u64::MAX)
+    );
+
+    let to_amount = |mint: &mut SolanaAccount, ui_amount| {
+        common::do_process_instruction(
+            ui_amount_to_amount(&token::id(), &mint_key, ui_amount).unwrap(),
+            vec![mint],
+        )
+        .map(|()| {
+            let return_data = common::take_return_data();
+            u64::from_le_bytes(return_data[..].try_into().unwrap())
+        })
+    };
+    let mut mint = common::mint_account(None, 0, 0);
+    assert_eq!(Ok(1_230), to_amount(&mut mint, "1230"));
+    assert_eq!(Ok(1_230), to_amount(&mut mint, "1230.000"));
+    assert_eq!(
+        Err(ProgramError::InvalidArgument),
+        to_amount(&mut mint, "1.5")
+    );
+    let mut mint = common::mint_account(None, 0, 9);
+    assert_eq!(Ok(1), to_amount(&mut mint, "0.000000001"));
+    assert_eq!(Ok(1), to_amount(&mut mint, ".000000001"));
+    assert_eq!(Ok(1_230_000_000), to_amount(&mut mint, "1.23"));
+    assert_eq!(Ok(u64::MAX), to_amount(&mut mint, "18446744073.709551615"));
+    assert_eq!(
+        Err(TokenError::Overflow.into()),
+        to_amount(&mut mint, "18446744073.709551616")
+    );
+    for malformed in ["", ".", "1.2.3", "-1", "1e9", " 1"].iter() {
+        assert_eq!(
+            Err(ProgramError::InvalidArgument),
+            to_amount(&mut mint, malformed)
+        );
+    }
+    let mut mint = common::mint_account(None, 0, MAX_DECIMALS);
+    assert_eq!(Ok(10_000_000_000_000_000_000), to_amount(&mut mint, "1"));
+    assert_eq!(Ok(u64::MAX), to_amount(&mut mint, "1.8446744073709551615"));
+    assert_eq!(
+        Err(TokenError::Overflow.into()),
+        to_amount(&mut mint, "1.8446744073709551616")
+    );
+    assert_eq!(Err(TokenError::Overflow.into()), to_amount(&mut mint, "2"));
+    assert_eq!(
+        Err(ProgramError::InvalidArgument),
+        to_amount(&mut mint, "0.00000000000000000001")
+    );
+}
+
+#[test]
+fn test_get_account_data_size() {
+    common::set_program_stubs();
+    let mint_key = Pubkey::new_unique();
+    let get_size = |mint: &mut SolanaAccount| {
+        common::do_process_instruction(
+            get_account_data_size(&token::id(), &mint_key).unwrap(),
+            vec![mint],
+        )
+        .map(|()| u64::from_le_bytes(common::take_return_data()[..].try_into().unwrap()))
+    };
+
+    let mut mint = common::mint_account(None, 0, 2);
+    assert_eq!(Ok(Account::get_packed_len() as u64), get_size(&mut mint));
+    #[cfg(not(feature = "account-checksum"))]
+    assert_eq!(Ok(165), get_size(&mut mint));
+
+    let owner_key = Pubkey::new_unique();
+    let mut mint = pausable_mint(&owner_key, &owner_key);
+    assert_eq!(Ok(Account::LEN as u64 + 1 + 4), get_size(&mut mint));
+
+    let mut mint = common::uninitialized_account(Mint::LEN);
+    assert_eq!(Err(TokenError::InvalidMint.into()), get_size(&mut mint));
+}
+
+#[test]
+fn test_get_account_balance_after_transfer() {
+    common::set_program_stubs();
+    let mint_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let source_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let mut source = common::token_account(&mint_key, &owner_key, 100);
+    let mut destination = common::token_account(&mint_key, &owner_key, 5);
+    let mut owner = SolanaAccount::default();
+
+    common::do_process_instruction(
+        transfer(
+            &token::id(),
+            &source_key,
+            &destination_key,
+            &owner_key,
+            &[],
+            40,
+        )
+        .unwrap(),
+        vec![&mut source, &mut destination, &mut owner],
+    )
+    .unwrap();
+
+    let get_balance = |account_key: &Pubkey, account: &mut SolanaAccount| {
+        common::do_process_instruction(
+            get_account_balance(&token::id(), account_key).unwrap(),
+            vec![account],
+        )
+        .map(|()| u64::from_le_bytes(common::take_return_data()[..].try_into().unwrap()))
+    };
+    assert_eq!(Ok(60), get_balance(&source_key, &mut source));
+    assert_eq!(Ok(45), get_balance(&destination_key, &mut destination));
+
+    let mut uninitialized = common::uninitialized_account(Account::LEN);
+    assert_eq!(
+        Err(ProgramError::UninitializedAccount),
+        get_balance(&source_key, &mut uninitialized)
+    );
+    source.owner = Pubkey::new_unique();
+    assert_eq!(
+        Err(ProgramError::IncorrectProgramId),
+        get_balance(&source_key, &mut source)
+    );
+}
+
+#[test]
+fn test_get_account_state_of_frozen_account() {
+    common::set_program_stubs();
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let freeze_authority_key = Pubkey::new_unique();
+    let mut mint = common::packed_account(&Mint {
+        freeze_authority: COption::Some(freeze_authority_key),
+        is_initialized: true,
+        ..Mint::default()
+    });
+    let mut account = common::token_account(&mint_key, &owner_key, 25);
+    let mut freeze_authority = SolanaAccount::default();
+
+    let get_state = |account: &mut SolanaAccount| {
+        common::do_process_instruction(
+            get_account_state(&program_id, &account_key).unwrap(),
+            vec![account],
+        )
+        .map(|()| common::take_return_data())
+    };
+    let mut expected = vec![AccountState::Initialized as u8, 0];
+    expected.extend_from_slice(&25u64.to_le_bytes());
+    assert_eq!(Ok(expected.clone()), get_state(&mut account));
+
+    common::do_process_instruction(
+        freeze_account(
+            &program_id,
+            &account_key,
+            &mint_key,
+            &freeze_authority_key,
+            &[],
+        )
+        .unwrap(),
+        vec![&mut account, &mut mint, &mut freeze_authority],
+    )
+    .unwrap();
+    expected[0] = AccountState::Frozen as u8;
+    assert_eq!(Ok(expected), get_state(&mut account));
+
+    let mut native = common::native_account(&native_mint::id(), &owner_key, 7);
+    let state = get_state(&mut native).unwrap();
+    assert_eq!(AccountState::Initialized as u8, state[0]);
+    assert_eq!(1, state[1]);
+    assert_eq!(7, u64::from_le_bytes(state[2..].try_into().unwrap()));
+
+    let mut uninitialized = common::uninitialized_account(Account::LEN);
+    assert_eq!(
+        Err(ProgramError::UninitializedAccount),
+        get_state(&mut uninitialized)
+    );
+}
+
+#[test]
+fn test_immutable_owner() {
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut mint = common::mint_account(Some(&owner_key), 0, 2);
+    let mut owner = SolanaAccount::default();
+
+    // there is no room for the marker in a base-sized account
+    let mut account = common::uninitialized_account(Account::LEN);
+    assert_eq!(
+        Err(ProgramError::AccountDataTooSmall),
+        common::do_process_instruction(
+            initialize_immutable_owner(&token::id(), &account_key).unwrap(),
+            vec![&mut account],
+        )
+    );
+
+    // nor is the marker ever added to data of a multisig's length
+    let mut account = common::uninitialized_account(Multisig::LEN);
+    assert_eq!(
+        Err(ProgramError::InvalidAccountData),
+        common::do_process_instruction(
+            initialize_immutable_owner(&token::id(), &account_key).unwrap(),
+            vec![&mut account],
+        )
+    );
+
+    let account_len =
+        ExtensionType::get_account_len(Account::LEN + 1, &[ExtensionType::ImmutableOwner]);
+    let mut account = common::uninitialized_account(account_len);
+    common::do_process_instruction(
+        initialize_immutable_owner(&token::id(), &account_key).unwrap(),
+        vec![&mut account],
+    )
+    .unwrap();
+    init_token_account(&mut account, &mint_key, &mut mint, &owner_key).unwrap();
+    assert_eq!(
+        Ok(vec![ExtensionType::ImmutableOwner]),
+        get_extension_types(&account.data[Account::LEN + 1..])
+    );
+    assert_eq!(
+        Err(TokenError::AlreadyInUse.into()),
+        common::do_process_instruction(
+            initialize_immutable_owner(&token::id(), &account_key).unwrap(),
+            vec![&mut account],
+        )
+    );
+
+    let new_key = Pubkey::new_unique();
+    let mut set_authority_to = |authority_type| {
+        common::do_process_instruction(
+            set_authority(
+                &token::id(),
+                &account_key,
+                Some(&new_key),
+                authority_type,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut account, &mut owner],
+        )
+    };
+    assert_eq!(
+        Err(TokenError::ImmutableOwner.into()),
+        set_authority_to(AuthorityType::AccountOwner)
+    );
+    set_authority_to(AuthorityType::CloseAccount).unwrap();
+
+    let account = Account::unpack_versioned(&account.data).unwrap();
+    assert_eq!(owner_key, account.owner);
+    assert_eq!(COption::Some(new_key), account.close_authority);
+}
+
+#[test]
+fn test_disable_mint_authority() {
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut mint = common::mint_account(Some(&owner_key), 0, 2);
+    let mut account = common::token_account(&mint_key, &owner_key, 0);
+    let mut owner = SolanaAccount::default();
+
+    common::do_process_instruction(
+        set_authority(
+            &token::id(),
+            &mint_key,
+            None,
+            AuthorityType::MintTokens,
+            &owner_key,
+            &[],
+        )
+        .unwrap(),
+        vec![&mut mint, &mut owner],
+    )
+    .unwrap();
+    assert_eq!(
+        COption::None,
+        Mint::unpack(&mint.data).unwrap().mint_authority
+    );
+
+    assert_eq!(
+        Err(TokenError::FixedSupply.into()),
+        common::do_process_instruction(
+            mint_to(&token::id(), &mint_key, &account_key, &owner_key, &[], 100).unwrap(),
+            vec![&mut mint, &mut account, &mut owner],
+        )
+    );
+
+    // the authority cannot be restored either
+    assert_eq!(
+        Err(TokenError::FixedSupply.into()),
+        common::do_process_instruction(
+            set_authority(
+                &token::id(),
+                &mint_key,
+                Some(&owner_key),
+                AuthorityType::MintTokens,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut mint, &mut owner],
+        )
+    );
+    assert_eq!(0, Mint::unpack(&mint.data).unwrap().supply);
+}
+
+#[test]
+fn test_set_mint_authority_with_multisig() {
+    let mint_key = Pubkey::new_unique();
+    let multisig_key = Pubkey::new_unique();
+    let new_authority_key = Pubkey::new_unique();
+    let signer_keys = [
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+    let mut multisig = common::uninitialized_account(Multisig::LEN);
+    init_multisig(&mut multisig, 2, &signer_keys).unwrap();
+    let mut mint = common::mint_account(Some(&multisig_key), 0, 2);
+    let mut signers = vec![SolanaAccount::default(); signer_keys.len()];
+
+    let mut set_mint_authority = |signer_keys: &[&Pubkey]| {
+        let instruction = set_authority(
+            &token::id(),
+            &mint_key,
+            Some(&new_authority_key),
+            AuthorityType::MintTokens,
+            &multisig_key,
+            signer_keys,
+        )
+        .unwrap();
+        // the mint, the multisig authority, then its signers
+        assert_eq!(2 + signer_keys.len(), instruction.accounts.len());
+        let mut accounts = vec![&mut mint, &mut multisig];
+        accounts.extend(signers.iter_mut().take(signer_keys.len()));
+        common::do_process_instruction(instruction, accounts)
+    };
+    assert_eq!(
+        Err(ProgramError::MissingRequiredSignature),
+        set_mint_authority(&[&signer_keys[0]])
+    );
+    set_mint_authority(&[&signer_keys[0], &signer_keys[2]]).unwrap();
+
+    assert_eq!(
+        COption::Some(new_authority_key),
+        Mint::unpack(&mint.data).unwrap().mint_authority
+    );
+}
+
+#[test]
+fn test_freeze_and_thaw_with_multisig_freeze_authority() {
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let multisig_key = Pubkey::new_unique();
+    let signer_keys = [
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+    let mut multisig = common::uninitialized_account(Multisig::LEN);
+    init_multisig(&mut multisig, 2, &signer_keys).unwrap();
+    let mut mint = common::packed_account(&Mint {
+        freeze_authority: COption::Some(multisig_key),
+        is_initialized: true,
+        ..Mint::default()
+    });
+    let mut account = common::token_account(&mint_key, &Pubkey::new_unique(), 42);
+    let mut signers = vec![SolanaAccount::default(); signer_keys.len()];
+
+    let mut toggle = |freeze: bool, signer_keys: &[&Pubkey]| {
+        let builder = if freeze { freeze_account } else { thaw_account };
+        let instruction = builder(
+            &token::id(),
+            &account_key,
+            &mint_key,
+            &multisig_key,
+            signer_keys,
+        )
+        .unwrap();
+        // the account, the mint, the multisig authority, then its signers
+        assert_eq!(3 + signer_keys.len(), instruction.accounts.len());
+        assert!(!instruction.accounts[2].is_signer);
+        let mut accounts = vec![&mut account, &mut mint, &mut multisig];
+        accounts.extend(signers.iter_mut().take(signer_keys.len()));
+        common::do_process_instruction(instruction, accounts)?;
+        Ok(Account::unpack(&account.data).unwrap().is_frozen())
+    };
+    assert_eq!(
+        Err(ProgramError::MissingRequiredSignature),
+        toggle(true, &[&signer_keys[1]])
+    );
+    assert_eq!(Ok(true), toggle(true, &[&signer_keys[0], &signer_keys[1]]));
+    assert_eq!(
+        Err(ProgramError::MissingRequiredSignature),
+        toggle(false, &[&signer_keys[2]])
+    );
+    assert_eq!(
+        Ok(false),
+        toggle(false, &[&signer_keys[1], &signer_keys[2]])
+    );
+}
+
+#[test]
+fn test_hand_over_freeze_authority_while_frozen() {
+    let program_id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let old_authority_key = Pubkey::new_unique();
+    let new_authority_key = Pubkey::new_unique();
+    let mut mint = common::packed_account(&Mint {
+        freeze_authority: COption::Some(old_authority_key),
+        is_initialized: true,
+        ..Mint::default()
+    });
+    let mut account = common::packed_account(&Account {
+        mint: mint_key,
+        owner: Pubkey::new_unique(),
+        state: AccountState::Frozen,
+        ..Account::default()
+    });
+    let mut authority = SolanaAccount::default();
+
+    let mut set_freeze_authority = |new_authority: Option<&Pubkey>, mint: &mut SolanaAccount| {
+        common::do_process_instruction(
+            set_authority(
+                &program_id,
+                &mint_key,
+                new_authority,
+                AuthorityType::FreezeAccount,
+                &new_authority_key,
+                &[],
+            )
+            .unwrap(),
+            vec![mint, &mut authority],
+        )
+    };
+    common::do_process_instruction(
+        set_authority(
+            &program_id,
+            &mint_key,
+            Some(&new_authority_key),
+            AuthorityType::FreezeAccount,
+            &old_authority_key,
+            &[],
+        )
+        .unwrap(),
+        vec![&mut mint, &mut SolanaAccount::default()],
+    )
+    .unwrap();
+
+    // the new authority thaws the account frozen under the old one
+    common::do_process_instruction(
+        thaw_account(
+            &program_id,
+            &account_key,
+            &mint_key,
+            &new_authority_key,
+            &[],
+        )
+        .unwrap(),
+        vec![&mut account, &mut mint, &mut SolanaAccount::default()],
+    )
+    .unwrap();
+    assert!(!Account::unpack(&account.data).unwrap().is_frozen());
+
+    set_freeze_authority(None, &mut mint).unwrap();
+    assert_eq!(
+        Err(TokenError::MintCannotFreeze.into()),
+        set_freeze_authority(Some(&new_authority_key), &mut mint)
+    );
+}
+
+#[test]
+fn test_owner_change_resets_delegation() {
+    let account_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let new_owner_key = Pubkey::new_unique();
+    let close_authority_key = Pubkey::new_unique();
+    let mut owner = SolanaAccount::default();
+
+    let change_owner = |mut account: SolanaAccount, owner: &mut SolanaAccount| {
+        let mut state = Account::unpack(&account.data).unwrap();
+        state.delegate = COption::Some(Pubkey::new_unique());
+        state.delegated_amount = 5;
+        state.close_authority = COption::Some(close_authority_key);
+        Account::pack(state, &mut account.data).unwrap();
+
+        common::do_process_instruction(
+            set_authority(
+                &token::id(),
+                &account_key,
+                Some(&new_owner_key),
+                AuthorityType::AccountOwner,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut account, owner],
+        )
+        .unwrap();
+        Account::unpack(&account.data).unwrap()
+    };
+
+    let account = change_owner(
+        common::token_account(&Pubkey::new_unique(), &owner_key, 10),
+        &mut owner,
+    );
+    assert_eq!(new_owner_key, account.owner);
+    assert_eq!(COption::None, account.delegate);
+    assert_eq!(0, account.delegated_amount);
+    assert_eq!(COption::Some(close_authority_key), account.close_authority);
+
+    let account = change_owner(
+        common::native_account(&native_mint::id(), &owner_key, 10),
+        &mut owner,
+    );
+    assert_eq!(new_owner_key, account.owner);
+    assert_eq!(COption::None, account.delegate);
+    assert_eq!(0, account.delegated_amount);
+    assert_eq!(COption::None, account.close_authority);
+}
+
+fn validate_multisig(signer_slots: &[Pubkey], signer_keys: &[Pubkey]) -> ProgramResult {
+    let multisig_key = Pubkey::new_unique();
+    let mut state = Multisig {
+        m: 2,
+        n: signer_slots.len() as u8,
+        is_initialized: true,
+        ..Multisig::default()
+    };
+    state.signers[..signer_slots.len()].copy_from_slice(signer_slots);
+    let mut multisig = common::packed_account(&state);
+    let mut signers = vec![SolanaAccount::default(); signer_keys.len()];
+    let mut accounts = vec![(&multisig_key, false, &mut multisig)];
+    accounts.extend(
+        signer_keys
+            .iter()
+            .zip(signers.iter_mut())
+            .map(|(key, account)| (key, true, account)),
+    );
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    Processor::validate_owner(
+        &token::id(),
+        &multisig_key,
+        &account_infos[0],
+        &account_infos[1..],
+    )
+}
+
+#[test]
+fn test_validate_owner_counts_each_signer_once() {
+    let keys = [
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+
+    validate_multisig(&keys, &[keys[0], keys[2]]).unwrap();
+    assert_eq!(
+        Err(ProgramError::MissingRequiredSignature),
+        validate_multisig(&keys, &[keys[0], keys[0]])
+    );
+
+    // a key filling two slots of a 2-of-3 multisig still only counts once
+    let slots = [keys[0], keys[0], keys[1]];
+    assert_eq!(
+        Err(ProgramError::MissingRequiredSignature),
+        validate_multisig(&slots, &[keys[0]])
+    );
+    validate_multisig(&slots, &[keys[0], keys[1]]).unwrap();
+}
+
+#[test]
+fn test_self_transfer_by_delegate() {
+    let program_id = token::id();
+    let account_key = Pubkey::new_unique();
+    let delegate_key = Pubkey::new_unique();
+    let mut account = common::packed_account(&Account {
+        mint: Pubkey::new_unique(),
+        owner: Pubkey::new_unique(),
+        amount: 100,
+        delegate: COption::Some(delegate_key),
+        delegated_amount: 50,
+        state: AccountState::Initialized,
+        ..Account::default()
+    });
+    let mut same_account = account.clone();
+    let mut delegate = SolanaAccount::default();
+
+    let mut accounts = [
+        (&account_key, false, &mut account),
+        (&account_key, false, &mut same_account),
+        (&delegate_key, true, &mut delegate),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    // the delegated amount still bounds the transfer
+    assert_eq!(
+        Err(TokenError::InsufficientFunds.into()),
+        Processor::process_transfer(&program_id, &account_infos, 51, None)
+    );
+    Processor::process_transfer(&program_id, &account_infos, 50, None).unwrap();
+
+    let account = Account::unpack(&account.data).unwrap();
+    assert_eq!(100, account.amount);
+    assert_eq!(COption::Some(delegate_key), account.delegate);
+    assert_eq!(50, account.delegated_amount);
+}
+
+#[test]
+fn test_initialize_with_injected_rent() {
+    common::set_program_stubs();
+    let rent = Rent {
+        lamports_per_byte_year: Rent::default().lamports_per_byte_year * 2,
+        ..Rent::default()
+    };
+    let mint_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+
+    // through the rent sysvar account
+    let mut rent_sysvar = common::rent_sysvar_with(&rent);
+    let mut mint = common::uninitialized_account(Mint::LEN);
+    mint.lamports = rent.minimum_balance(Mint::LEN) - 1;
+    let instruction = initialize_mint(&token::id(), &mint_key, &owner_key, None, 2).unwrap();
+    assert_eq!(
+        Err(TokenError::NotRentExempt.into()),
+        common::do_process_instruction(instruction.clone(), vec![&mut mint, &mut rent_sysvar])
+    );
+    mint.lamports += 1;
+    common::do_process_instruction(instruction, vec![&mut mint, &mut rent_sysvar]).unwrap();
+
+    // and through the sysvar cache
+    common::set_rent(&rent);
+    let account_key = Pubkey::new_unique();
+    let mut account = common::uninitialized_account(Account::LEN);
+    account.lamports = rent.minimum_balance(Account::LEN) - 1;
+    let instruction =
+        initialize_account3(&token::id(), &account_key, &mint_key, &owner_key).unwrap();
+    assert_eq!(
+        Err(TokenError::NotRentExempt.into()),
+        common::do_process_instruction(instruction.clone(), vec![&mut account, &mut mint])
+    );
+    account.lamports += 1;
+    common::do_process_instruction(instruction, vec![&mut account, &mut mint]).unwrap();
+    common::set_rent(&Rent::default());
+
+    assert!(Account::unpack(&account.data).unwrap().is_initialized());
+}
+
+#[test]
+fn test_initialize_rent_sources() {
+    common::set_program_stubs();
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut mint = common::mint_account(Some(&owner_key), 0, 2);
+
+    // an account passed in place of the rent sysvar is rejected
+    let not_rent_key = Pubkey::new_unique();
+    let mut not_rent = common::rent_sysvar();
+    let mut owner = SolanaAccount::default();
+    let mut account = common::uninitialized_account(Account::LEN);
+    let mut accounts = [
+        (&account_key, false, &mut account),
+        (&mint_key, false, &mut mint),
+        (&owner_key, false, &mut owner),
+        (&not_rent_key, false, &mut not_rent),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    assert_eq!(
+        Err(ProgramError::InvalidArgument),
+        Processor::process_initialize_account(&account_infos)
+    );
+
+    // without the sysvar the rent comes from the sysvar cache
+    common::do_process_instruction(
+        initialize_account3(&token::id(), &account_key, &mint_key, &owner_key).unwrap(),
+        vec![&mut account, &mut mint],
+    )
+    .unwrap();
+    assert_eq!(owner_key, Account::unpack(&account.data).unwrap().owner);
+
+    let multisig_key = Pubkey::new_unique();
+    let mut multisig = common::uninitialized_account(Multisig::LEN);
+    let mut signer = SolanaAccount::default();
+    let mut accounts = [
+        (&multisig_key, false, &mut multisig),
+        (&owner_key, false, &mut signer),
+    ];
+    let account_infos = create_is_signer_account_infos(&mut accounts);
+    Processor::process_initialize_multisig2(&account_infos, 1).unwrap();
+    assert_eq!(1, Multisig::unpack(&multisig.data).unwrap().n);
+}
+
+#[test]
+fn test_initialize_account3_owner_from_data() {
+    common::set_program_stubs();
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mut mint = common::mint_account(Some(&Pubkey::new_unique()), 0, 2);
+    let mut account = common::uninitialized_account(Account::LEN);
+    let instruction =
+        initialize_account3(&token::id(), &account_key, &mint_key, &owner_key).unwrap();
+
+    // the mint is still required
+    assert_eq!(
+        Err(ProgramError::NotEnoughAccountKeys),
+        common::do_process_instruction(instruction.clone(), vec![&mut account])
+    );
+
+    // neither the owner nor the rent sysvar is passed
+    common::do_process_instruction(instruction, vec![&mut account, &mut mint]).unwrap();
+    let account = Account::unpack(&account.data).unwrap();
+    assert_eq!(owner_key, account.owner);
+    assert_eq!(mint_key, account.mint);
+    assert_eq!(AccountState::Initialized, account.state);
+}
+
+/// Submits `instruction` without its last account and checks that it fails with
+/// `NotEnoughAccountKeys` before touching any of the other accounts
+fn assert_last_account_required(mut instruction: Instruction, mut accounts: Vec<SolanaAccount>) {
+    assert_eq!(instruction.accounts.len(), accounts.len());
+    instruction.accounts.pop();
+    accounts.pop();
+    let before = accounts.clone();
+    assert_eq!(
+        Err(ProgramError::NotEnoughAccountKeys),
+        common::do_process_instruction(instruction.clone(), accounts.iter_mut().collect()),
+        "{:?}",
+        TokenInstruction::unpack(&instruction.data)
+    );
+    assert_eq!(before, accounts);
+}
+
+#[test]
+fn test_missing_accounts_leave_state_untouched() {
+    common::set_program_stubs();
+    let id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let source_key = Pubkey::new_unique();
+    let destination_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mint = || common::mint_account(Some(&owner_key), 100, 2);
+    let source = || common::token_account(&mint_key, &owner_key, 100);
+    let destination = || common::token_account(&mint_key, &owner_key, 0);
+    let empty_account = || common::token_account(&mint_key, &owner_key, 0);
+    let new_mint = || common::uninitialized_account(Mint::LEN);
+    let new_account = || common::uninitialized_account(Account::LEN);
+    let owner = SolanaAccount::default;
+
+    let cases = vec![
+        (
+            initialize_mint(&id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![new_mint(), common::rent_sysvar()],
+        ),
+        (
+            initialize_mint_with_supply(
+                &id,
+                &mint_key,
+                &owner_key,
+                None,
+                2,
+                &source_key,
+                &owner_key,
+                5,
+            )
+            .unwrap(),
+            vec![new_mint(), common::rent_sysvar(), new_account(), owner()],
+        ),
+        (
+            initialize_mint2(&id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![new_mint()],
+        ),
+        (
+            initialize_account(&id, &source_key, &mint_key, &owner_key).unwrap(),
+            vec![new_account(), mint(), owner(), common::rent_sysvar()],
+        ),
+        (
+            initialize_account2(&id, &source_key, &mint_key, &owner_key).unwrap(),
+            vec![new_account(), mint(), common::rent_sysvar()],
+        ),
+        (
+            initialize_account3(&id, &source_key, &mint_key, &owner_key).unwrap(),
+            vec![new_account(), mint()],
+        ),
+        (
+            initialize_immutable_owner(&id, &source_key).unwrap(),
+            vec![new_account()],
+        ),
+        (
+            transfer(&id, &source_key, &destination_key, &owner_key, &[], 1).unwrap(),
+            vec![source(), destination(), owner()],
+        ),
+        (
+            transfer_checked(
+                &id,
+                &source_key,
+                &mint_key,
+                &destination_key,
+                &owner_key,
+                &[],
+                1,
+                2,
+            )
+            .unwrap(),
+            vec![source(), mint(), destination(), owner()],
+        ),
+        (
+            transfer_checked_with_fee(
+                &id,
+                &source_key,
+                &mint_key,
+                &destination_key,
+                &Pubkey::new_unique(),
+                &owner_key,
+                &[],
+                2,
+                2,
+                1,
+            )
+            .unwrap(),
+            vec![source(), mint(), destination(), destination(), owner()],
+        ),
+        (
+            approve(&id, &source_key, &destination_key, &owner_key, &[], 1).unwrap(),
+            vec![source(), owner(), owner()],
+        ),
+        (
+            approve_checked(
+                &id,
+                &source_key,
+                &mint_key,
+                &destination_key,
+                &owner_key,
+                &[],
+                1,
+                2,
+            )
+            .unwrap(),
+            vec![source(), mint(), owner(), owner()],
+        ),
+        (
+            revoke(&id, &source_key, &owner_key, &[]).unwrap(),
+            vec![source(), owner()],
+        ),
+        (
+            set_authority(
+                &id,
+                &source_key,
+                Some(&destination_key),
+                AuthorityType::CloseAccount,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![source(), owner()],
+        ),
+        (
+            mint_to(&id, &mint_key, &source_key, &owner_key, &[], 1).unwrap(),
+            vec![mint(), source(), owner()],
+        ),
+        (
+            mint_to_checked(&id, &mint_key, &source_key, &owner_key, &[], 1, 2).unwrap(),
+            vec![mint(), source(), owner()],
+        ),
+        (
+            token::instruction::burn(&id, &source_key, &mint_key, &owner_key, &[], 1).unwrap(),
+            vec![source(), mint(), owner()],
+        ),
+        (
+            burn_checked(&id, &source_key, &mint_key, &owner_key, &[], 1, 2).unwrap(),
+            vec![source(), mint(), owner()],
+        ),
+        (
+            token::instruction::close_account(&id, &source_key, &destination_key, &owner_key, &[])
+                .unwrap(),
+            vec![empty_account(), owner(), owner()],
+        ),
+        (
+            burn_and_close(
+                &id,
+                &source_key,
+                &mint_key,
+                &destination_key,
+                &owner_key,
+                &[],
+                100,
+            )
+            .unwrap(),
+            vec![source(), mint(), owner(), owner()],
+        ),
+        (
+            freeze_account(&id, &source_key, &mint_key, &owner_key, &[]).unwrap(),
+            vec![source(), mint(), owner()],
+        ),
+        (
+            thaw_account(&id, &source_key, &mint_key, &owner_key, &[]).unwrap(),
+            vec![source(), mint(), owner()],
+        ),
+        (
+            withdraw_excess_lamports(&id, &source_key, &destination_key, &owner_key, &[]).unwrap(),
+            vec![source(), owner(), owner()],
+        ),
+        (
+            set_pause(&id, &mint_key, &owner_key, &[], true).unwrap(),
+            vec![mint(), owner()],
+        ),
+        (sync_native(&id, &source_key).unwrap(), vec![source()]),
+        (get_account_data_size(&id, &mint_key).unwrap(), vec![mint()]),
+        (
+            amount_to_ui_amount(&id, &mint_key, 1).unwrap(),
+            vec![mint()],
+        ),
+        (
+            ui_amount_to_amount(&id, &mint_key, "1").unwrap(),
+            vec![mint()],
+        ),
+        (
+            get_account_balance(&id, &source_key).unwrap(),
+            vec![source()],
+        ),
+        (get_account_state(&id, &source_key).unwrap(), vec![source()]),
+    ];
+    for (instruction, accounts) in cases {
+        assert_last_account_required(instruction, accounts);
+    }
+}
+
+#[test]
+fn test_multisig_set_authority_end_to_end() {
+    let mut harness = common::AccountHarness::default();
+    let mint_key = Pubkey::new_unique();
+    let multisig_key = Pubkey::new_unique();
+    let new_authority_key = Pubkey::new_unique();
+    let signer_keys = [
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+    harness.insert(sysvar::rent::id(), common::rent_sysvar());
+    harness.insert(multisig_key, common::uninitialized_account(Multisig::LEN));
+    harness.insert(mint_key, common::mint_account(Some(&multisig_key), 0, 2));
+    harness
+        .process(
+            &initialize_multisig(
+                &token::id(),
+                &multisig_key,
+                &signer_keys.iter().collect::<Vec<_>>(),
+                2,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+    let set_mint_authority = |signer_pubkeys: &[&Pubkey]| {
+        set_authority(
+            &token::id(),
+            &mint_key,
+            Some(&new_authority_key),
+            AuthorityType::MintTokens,
+            &multisig_key,
+            signer_pubkeys,
+        )
+        .unwrap()
+    };
+
+    // the same member passed twice still counts once
+    assert_eq!(
+        Err(ProgramError::MissingRequiredSignature),
+        harness.process(&set_mint_authority(&[&signer_keys[1], &signer_keys[1]]))
+    );
+    assert_eq!(
+        COption::Some(multisig_key),
+        harness.unpack::<Mint>(&mint_key).mint_authority
+    );
+
+    harness
+        .process(&set_mint_authority(&[&signer_keys[1], &signer_keys[2]]))
+        .unwrap();
+    assert_eq!(
+        COption::Some(new_authority_key),
+        harness.unpack::<Mint>(&mint_key).mint_authority
+    );
+}
+
+#[test]
+fn test_check_expected_decimals() {
+    let mint_key = Pubkey::new_unique();
+    let mint = Mint {
+        decimals: 6,
+        is_initialized: true,
+        ..Mint::default()
+    };
+
+    assert_eq!(
+        Ok(()),
+        Processor::check_expected_decimals(&mint, Some(6), &mint_key, &mint_key)
+    );
+    assert_eq!(
+        Ok(()),
+        Processor::check_expected_decimals(&mint, None, &mint_key, &mint_key)
+    );
+    assert_eq!(
+        Err(TokenError::MintDecimalsMismatch.into()),
+        Processor::check_expected_decimals(&mint, Some(9), &mint_key, &mint_key)
+    );
+
+    // the mint key is checked even when no decimals are expected, and first
+    let other_mint_key = Pubkey::new_unique();
+    for expected in [None, Some(6), Some(9)] {
+        assert_eq!(
+            Err(TokenError::MintMismatch.into()),
+            Processor::check_expected_decimals(&mint, expected, &other_mint_key, &mint_key)
+        );
+    }
+}
+
+#[test]
+fn test_uninitialized_accounts_rejected() {
+    let id = token::id();
+    let mint_key = Pubkey::new_unique();
+    let account_key = Pubkey::new_unique();
+    let other_key = Pubkey::new_unique();
+    let owner_key = Pubkey::new_unique();
+    let mint = || common::mint_account(Some(&owner_key), 100, 2);
+    let account = || common::token_account(&mint_key, &owner_key, 100);
+    let uninitialized = || common::uninitialized_account(Account::LEN);
+    let owner = SolanaAccount::default;
+
+    let cases = vec![
+        (
+            transfer(&id, &account_key, &other_key, &owner_key, &[], 1).unwrap(),
+            vec![uninitialized(), account(), owner()],
+        ),
+        (
+            transfer(&id, &account_key, &other_key, &owner_key, &[], 1).unwrap(),
+            vec![account(), uninitialized(), owner()],
+        ),
+        (
+            mint_to(&id, &mint_key, &account_key, &owner_key, &[], 1).unwrap(),
+            vec![mint(), uninitialized(), owner()],
+        ),
+        (
+            token::instruction::burn(&id, &account_key, &mint_key, &owner_key, &[], 1).unwrap(),
+            vec![uninitialized(), mint(), owner()],
+        ),
+        (
+            approve(&id, &account_key, &other_key, &owner_key, &[], 1).unwrap(),
+            vec![uninitialized(), owner(), owner()],
+        ),
+    ];
+    for (instruction, mut accounts) in cases {
+        let before = accounts.clone();
+        assert_eq!(
+            Err(ProgramError::UninitializedAccount),
+            common::do_process_instruction(instruction, accounts.iter_mut().collect())
+        );
+        assert_eq!(before, accounts);
+    }
+}
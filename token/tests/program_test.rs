@@ -0,0 +1,166 @@
+//! Runs the mint, transfer and burn flow through solana-program-test's
+//! `BanksClient`, so that the instructions go through a bank's transaction
+//! processing rather than the in-memory account harness
+
+#![cfg(feature = "test-bpf")]
+
+use solana_program::{
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    system_instruction,
+};
+use solana_program_test::{processor, tokio, BanksClient, ProgramTest};
+use solana_sdk::{
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use token::{
+    instruction::{burn, initialize_account, initialize_mint, mint_to, transfer},
+    processor::Processor,
+    state::{Account, Mint},
+};
+
+async fn process(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) {
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    transaction.sign(&all_signers, recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+}
+
+async fn create_account(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    account: &Keypair,
+    space: usize,
+    instruction: Instruction,
+) {
+    let rent = banks_client.get_rent().await.unwrap();
+    process(
+        banks_client,
+        payer,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &account.pubkey(),
+                rent.minimum_balance(space),
+                space as u64,
+                &token::id(),
+            ),
+            instruction,
+        ],
+        &[account],
+    )
+    .await;
+}
+
+async fn unpack<T: Pack + IsInitialized>(banks_client: &mut BanksClient, key: &Pubkey) -> T {
+    let account = banks_client.get_account(*key).await.unwrap().unwrap();
+    T::unpack(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn test_mint_transfer_burn() {
+    let program_test = ProgramTest::new("token", token::id(), processor!(Processor::process));
+    let (mut banks_client, payer, _recent_blockhash) = program_test.start().await;
+    let mint = Keypair::new();
+    let mint_authority = Keypair::new();
+    let alice = Keypair::new();
+    let alice_account = Keypair::new();
+    let bob = Keypair::new();
+    let bob_account = Keypair::new();
+
+    create_account(
+        &mut banks_client,
+        &payer,
+        &mint,
+        Mint::LEN,
+        initialize_mint(
+            &token::id(),
+            &mint.pubkey(),
+            &mint_authority.pubkey(),
+            None,
+            6,
+        )
+        .unwrap(),
+    )
+    .await;
+    for (account, owner) in [(&alice_account, &alice), (&bob_account, &bob)] {
+        create_account(
+            &mut banks_client,
+            &payer,
+            account,
+            Account::LEN,
+            initialize_account(
+                &token::id(),
+                &account.pubkey(),
+                &mint.pubkey(),
+                &owner.pubkey(),
+            )
+            .unwrap(),
+        )
+        .await;
+    }
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[mint_to(
+            &token::id(),
+            &mint.pubkey(),
+            &alice_account.pubkey(),
+            &mint_authority.pubkey(),
+            &[],
+            1_000,
+        )
+        .unwrap()],
+        &[&mint_authority],
+    )
+    .await;
+    process(
+        &mut banks_client,
+        &payer,
+        &[transfer(
+            &token::id(),
+            &alice_account.pubkey(),
+            &bob_account.pubkey(),
+            &alice.pubkey(),
+            &[],
+            400,
+        )
+        .unwrap()],
+        &[&alice],
+    )
+    .await;
+    let alice_state = unpack::<Account>(&mut banks_client, &alice_account.pubkey()).await;
+    let bob_state = unpack::<Account>(&mut banks_client, &bob_account.pubkey()).await;
+    assert_eq!(600, alice_state.amount);
+    assert_eq!(400, bob_state.amount);
+
+    process(
+        &mut banks_client,
+        &payer,
+        &[burn(
+            &token::id(),
+            &bob_account.pubkey(),
+            &mint.pubkey(),
+            &bob.pubkey(),
+            &[],
+            150,
+        )
+        .unwrap()],
+        &[&bob],
+    )
+    .await;
+    let bob_state = unpack::<Account>(&mut banks_client, &bob_account.pubkey()).await;
+    let mint_state = unpack::<Mint>(&mut banks_client, &mint.pubkey()).await;
+    assert_eq!(250, bob_state.amount);
+    assert_eq!(850, mint_state.supply);
+}
@@ -0,0 +1,283 @@
+use solana_program::{
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+};
+use token::{
+    error::TokenError,
+    instruction::MAX_SIGNERS,
+    state::{Account, AccountState, Mint, Multisig},
+};
+
+fn initialized_account() -> Account {
+    Account {
+        mint: Pubkey::new_unique(),
+        owner: Pubkey::new_unique(),
+        amount: 42,
+        state: AccountState::Initialized,
+        ..Account::default()
+    }
+}
+
+#[test]
+fn test_unpack_versioned() {
+    let account = initialized_account();
+    let mut data = vec![0; Account::LEN];
+    account.pack_into_slice(&mut data);
+    assert_eq!(account, Account::unpack_versioned(&data).unwrap());
+
+    data.push(0);
+    assert_eq!(account, Account::unpack_versioned(&data).unwrap());
+
+    data[Account::LEN] = 1;
+    assert_eq!(
+        Err(TokenError::InvalidState.into()),
+        Account::unpack_versioned(&data)
+    );
+
+    assert_eq!(
+        Err(ProgramError::InvalidAccountData),
+        Account::unpack_versioned(&data[..Account::LEN - 1])
+    );
+}
+
+#[test]
+fn test_account_coption_encoding() {
+    let delegate = Pubkey::new_from_array([9; 32]);
+    let mut account = initialized_account();
+    let mut data = vec![0; Account::LEN];
+
+    account.pack_into_slice(&mut data);
+    assert_eq!([0; 36], data[72..108]);
+    assert_eq!([0; 12], data[109..121]);
+    assert_eq!([0; 36], data[129..165]);
+
+    account.delegate = COption::Some(delegate);
+    account.is_native = COption::Some(0x0102);
+    account.close_authority = COption::Some(delegate);
+    account.pack_into_slice(&mut data);
+    let mut expected_key = vec![1, 0, 0, 0];
+    expected_key.extend_from_slice(&[9; 32]);
+    assert_eq!(expected_key[..], data[72..108]);
+    assert_eq!([1, 0, 0, 0, 2, 1, 0, 0, 0, 0, 0, 0], data[109..121]);
+    assert_eq!(expected_key[..], data[129..165]);
+    assert_eq!(account, Account::unpack_from_slice(&data).unwrap());
+
+    // with a checksum, tampered tags are reported as corruption instead
+    if cfg!(feature = "account-checksum") {
+        return;
+    }
+    data[72] = 2;
+    assert_eq!(
+        Err(ProgramError::InvalidAccountData),
+        Account::unpack_from_slice(&data)
+    );
+    data[72] = 1;
+    data[73] = 1;
+    assert_eq!(
+        Err(ProgramError::InvalidAccountData),
+        Account::unpack_from_slice(&data)
+    );
+}
+
+#[cfg(feature = "account-checksum")]
+#[test]
+fn test_account_checksum() {
+    assert_eq!(169, Account::LEN);
+
+    let account = initialized_account();
+    let mut data = vec![0; Account::LEN];
+    // zeroed data has not been written yet and carries no checksum
+    assert_eq!(
+        Account::default(),
+        Account::unpack_unchecked(&data).unwrap()
+    );
+
+    Account::pack(account, &mut data).unwrap();
+    assert_eq!(account, Account::unpack(&data).unwrap());
+
+    for i in 0..Account::LEN {
+        let mut corrupted = data.clone();
+        corrupted[i] ^= 0x01;
+        assert_eq!(
+            Err(TokenError::InvalidState.into()),
+            Account::unpack_unchecked(&corrupted),
+            "flipped byte {} not detected",
+            i
+        );
+    }
+}
+
+#[cfg(not(feature = "account-checksum"))]
+#[test]
+fn test_account_golden_bytes() {
+    let mut data = vec![0; Account::LEN];
+    data[0..32].copy_from_slice(&[1; 32]);
+    data[32..64].copy_from_slice(&[2; 32]);
+    data[64..72].copy_from_slice(&[0xef, 0xcd, 0xab, 0x89, 0x67, 0x45, 0x23, 0x01]);
+    data[72..76].copy_from_slice(&[1, 0, 0, 0]);
+    data[76..108].copy_from_slice(&[3; 32]);
+    data[108] = 2;
+    data[109..113].copy_from_slice(&[1, 0, 0, 0]);
+    data[113..121].copy_from_slice(&[0xf0, 0x1d, 0x1f, 0, 0, 0, 0, 0]);
+    data[121..129].copy_from_slice(&[0xff; 8]);
+    data[129..133].copy_from_slice(&[1, 0, 0, 0]);
+    data[133..165].copy_from_slice(&[4; 32]);
+
+    let account = Account {
+        mint: Pubkey::new_from_array([1; 32]),
+        owner: Pubkey::new_from_array([2; 32]),
+        amount: 0x0123_4567_89ab_cdef,
+        delegate: COption::Some(Pubkey::new_from_array([3; 32])),
+        state: AccountState::Frozen,
+        is_native: COption::Some(2_039_280),
+        delegated_amount: u64::MAX,
+        close_authority: COption::Some(Pubkey::new_from_array([4; 32])),
+    };
+    assert_eq!(account, Account::unpack(&data).unwrap());
+
+    let mut packed = vec![0; Account::LEN];
+    Account::pack(account, &mut packed).unwrap();
+    assert_eq!(data, packed);
+}
+
+#[test]
+fn test_zero_key_delegate_is_normalized() {
+    let mut account = initialized_account();
+    account.delegate = COption::Some(Pubkey::default());
+    let mut data = vec![0; Account::LEN];
+    Account::pack(account, &mut data).unwrap();
+    // the Some tag is stored as is
+    assert_eq!([1, 0, 0, 0], data[72..76]);
+
+    let unpacked = Account::unpack(&data).unwrap();
+    assert_eq!(COption::None, unpacked.delegate);
+    assert_eq!(0, unpacked.delegated_amount);
+
+    // a zero key that still holds an allowance is kept as stored
+    account.delegated_amount = 5;
+    Account::pack(account, &mut data).unwrap();
+    assert_eq!(
+        COption::Some(Pubkey::default()),
+        Account::unpack(&data).unwrap().delegate
+    );
+}
+
+#[test]
+fn test_packed_len() {
+    assert_eq!(82, Mint::get_packed_len());
+    if cfg!(feature = "account-checksum") {
+        assert_eq!(169, Account::get_packed_len());
+    } else {
+        assert_eq!(165, Account::get_packed_len());
+    }
+    assert_eq!(355, Multisig::get_packed_len());
+}
+
+#[test]
+fn test_pack_round_trip() {
+    let mint = Mint {
+        mint_authority: COption::Some(Pubkey::new_unique()),
+        supply: 42,
+        decimals: 7,
+        is_initialized: true,
+        freeze_authority: COption::Some(Pubkey::new_unique()),
+    };
+    let mut data = vec![0; Mint::LEN];
+    Mint::pack(mint, &mut data).unwrap();
+    assert_eq!(mint, Mint::unpack(&data).unwrap());
+
+    let account = Account {
+        delegate: COption::Some(Pubkey::new_unique()),
+        delegated_amount: 7,
+        is_native: COption::Some(1),
+        close_authority: COption::Some(Pubkey::new_unique()),
+        state: AccountState::Frozen,
+        ..initialized_account()
+    };
+    assert!(account.is_frozen());
+    assert!(account.is_native());
+    let mut data = vec![0; Account::LEN];
+    Account::pack(account, &mut data).unwrap();
+    assert_eq!(account, Account::unpack(&data).unwrap());
+
+    let mut signers = [Pubkey::default(); MAX_SIGNERS];
+    for signer in signers.iter_mut().take(3) {
+        *signer = Pubkey::new_unique();
+    }
+    let multisig = Multisig {
+        m: 2,
+        n: 3,
+        is_initialized: true,
+        signers,
+    };
+    let mut data = vec![0; Multisig::LEN];
+    Multisig::pack(multisig, &mut data).unwrap();
+    assert_eq!(multisig, Multisig::unpack(&data).unwrap());
+
+    // unpacking checks the length and the initialized flag
+    assert_eq!(
+        Err(ProgramError::InvalidAccountData),
+        Multisig::unpack(&data[..Multisig::LEN - 1])
+    );
+    assert_eq!(
+        Err(ProgramError::UninitializedAccount),
+        Mint::unpack(&[0; Mint::LEN])
+    );
+}
+
+#[test]
+fn test_rent_exempt_reserve() {
+    let account = initialized_account();
+    assert!(!account.is_native());
+    assert_eq!(None, account.rent_exempt_reserve());
+
+    let account = Account {
+        is_native: COption::Some(2_039_280),
+        ..initialized_account()
+    };
+    assert!(account.is_native());
+    assert_eq!(Some(2_039_280), account.rent_exempt_reserve());
+
+    // a native account may hold a zero reserve, which still marks it native
+    let account = Account {
+        is_native: COption::Some(0),
+        ..initialized_account()
+    };
+    assert!(account.is_native());
+    assert_eq!(Some(0), account.rent_exempt_reserve());
+}
+
+#[test]
+fn test_account_state_byte() {
+    // mint, owner, amount and the delegate come before the state byte
+    const STATE_OFFSET: usize = 32 + 32 + 8 + 36;
+    for (state, is_initialized, is_frozen) in [
+        (AccountState::Uninitialized, false, false),
+        (AccountState::Initialized, true, false),
+        (AccountState::Frozen, true, true),
+    ] {
+        let account = Account {
+            state,
+            ..initialized_account()
+        };
+        assert_eq!(is_initialized, account.is_initialized());
+        assert_eq!(is_frozen, account.is_frozen());
+
+        let mut data = vec![0; Account::LEN];
+        Account::pack_into_slice(&account, &mut data);
+        assert_eq!(state as u8, data[STATE_OFFSET]);
+        assert_eq!(account, Account::unpack_unchecked(&data).unwrap());
+    }
+    assert_eq!(AccountState::Uninitialized, Account::default().state);
+
+    let mut data = vec![0; Account::LEN];
+    Account::pack(initialized_account(), &mut data).unwrap();
+    data[STATE_OFFSET] = 3;
+    let result = Account::unpack_unchecked(&data);
+    // with a checksum the corrupted byte is already caught by the checksum
+    #[cfg(not(feature = "account-checksum"))]
+    assert_eq!(Err(ProgramError::InvalidAccountData), result);
+    assert!(result.is_err());
+}
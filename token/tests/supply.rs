@@ -0,0 +1,177 @@
+//! Property tests checking that minting, transferring and burning conserve the
+//! mint supply
+
+mod common;
+
+use common::AccountHarness;
+use proptest::prelude::*;
+use solana_program::{instruction::Instruction, program_error::ProgramError, pubkey::Pubkey};
+use token::{
+    error::TokenError,
+    instruction::{burn, mint_to, transfer},
+    state::{Account, Mint},
+};
+
+const ACCOUNT_COUNT: usize = 3;
+
+#[derive(Clone, Debug)]
+enum Operation {
+    MintTo {
+        destination: usize,
+        amount: u64,
+    },
+    Transfer {
+        source: usize,
+        destination: usize,
+        amount: u64,
+    },
+    Burn {
+        source: usize,
+        amount: u64,
+    },
+}
+
+/// Amounts mixing small values with ones close enough to `u64::MAX` to overflow
+fn amount() -> impl Strategy<Value = u64> {
+    prop_oneof![0..1_000u64, u64::MAX / 2..=u64::MAX, Just(u64::MAX)]
+}
+
+fn operation() -> impl Strategy<Value = Operation> {
+    let index = 0..ACCOUNT_COUNT;
+    prop_oneof![
+        (index.clone(), amount()).prop_map(|(destination, amount)| Operation::MintTo {
+            destination,
+            amount
+        }),
+        (index.clone(), index.clone(), amount()).prop_map(|(source, destination, amount)| {
+            Operation::Transfer {
+                source,
+                destination,
+                amount,
+            }
+        }),
+        (index, amount()).prop_map(|(source, amount)| Operation::Burn { source, amount }),
+    ]
+}
+
+struct Ledger {
+    harness: AccountHarness,
+    mint_key: Pubkey,
+    mint_authority_key: Pubkey,
+    account_keys: Vec<Pubkey>,
+    owner_keys: Vec<Pubkey>,
+}
+
+impl Ledger {
+    fn new() -> Self {
+        let mut harness = AccountHarness::default();
+        let mint_key = Pubkey::new_unique();
+        let mint_authority_key = Pubkey::new_unique();
+        harness.insert(
+            mint_key,
+            common::mint_account(Some(&mint_authority_key), 0, 2),
+        );
+        let owner_keys = (0..ACCOUNT_COUNT)
+            .map(|_| Pubkey::new_unique())
+            .collect::<Vec<_>>();
+        let account_keys = owner_keys
+            .iter()
+            .map(|owner_key| {
+                let account_key = Pubkey::new_unique();
+                harness.insert(account_key, common::token_account(&mint_key, owner_key, 0));
+                account_key
+            })
+            .collect();
+        Self {
+            harness,
+            mint_key,
+            mint_authority_key,
+            account_keys,
+            owner_keys,
+        }
+    }
+
+    fn supply(&self) -> u64 {
+        self.harness.unpack::<Mint>(&self.mint_key).supply
+    }
+
+    fn balance(&self, index: usize) -> u64 {
+        self.harness
+            .unpack::<Account>(&self.account_keys[index])
+            .amount
+    }
+
+    fn total_balance(&self) -> u128 {
+        (0..ACCOUNT_COUNT)
+            .map(|index| u128::from(self.balance(index)))
+            .sum()
+    }
+
+    fn instruction(&self, operation: &Operation) -> Instruction {
+        match *operation {
+            Operation::MintTo {
+                destination,
+                amount,
+            } => mint_to(
+                &token::id(),
+                &self.mint_key,
+                &self.account_keys[destination],
+                &self.mint_authority_key,
+                &[],
+                amount,
+            ),
+            Operation::Transfer {
+                source,
+                destination,
+                amount,
+            } => transfer(
+                &token::id(),
+                &self.account_keys[source],
+                &self.account_keys[destination],
+                &self.owner_keys[source],
+                &[],
+                amount,
+            ),
+            Operation::Burn { source, amount } => burn(
+                &token::id(),
+                &self.account_keys[source],
+                &self.mint_key,
+                &self.owner_keys[source],
+                &[],
+                amount,
+            ),
+        }
+        .unwrap()
+    }
+}
+
+proptest! {
+    #[test]
+    fn test_operations_conserve_supply(
+        operations in prop::collection::vec(operation(), 1..32),
+    ) {
+        let mut ledger = Ledger::new();
+        for operation in operations {
+            let overflows = match operation {
+                Operation::MintTo { amount, .. } => ledger.supply().checked_add(amount).is_none(),
+                _ => false,
+            };
+            let affordable = match operation {
+                Operation::MintTo { .. } => true,
+                Operation::Transfer { source, amount, .. } | Operation::Burn { source, amount } => {
+                    amount <= ledger.balance(source)
+                }
+            };
+
+            let result = ledger.harness.process(&ledger.instruction(&operation));
+            if overflows {
+                prop_assert_eq!(Err(ProgramError::from(TokenError::Overflow)), result);
+            } else if affordable {
+                prop_assert_eq!(Ok(()), result);
+            } else {
+                prop_assert!(result.is_err());
+            }
+            prop_assert_eq!(u128::from(ledger.supply()), ledger.total_balance());
+        }
+    }
+}